@@ -0,0 +1,344 @@
+use crate::runs::RunHistory;
+use crate::tools::capture::{Capture, OutputLog, display_lines};
+use crate::tools::cargo_utils::{
+    RunOptions, fit_run_output, format_command, isolate_process_group, kill_process_tree,
+};
+use anyhow::{Result, anyhow};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Lifecycle of a background job
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum JobState {
+    #[default]
+    Running,
+    /// The process exited on its own
+    Finished {
+        exit_code: Option<i32>,
+        success: bool,
+    },
+    /// The process was killed by `job_cancel`
+    Cancelled,
+    /// The process was killed after exceeding its timeout
+    TimedOut,
+    /// The job could not be waited on
+    Failed(String),
+}
+
+impl JobState {
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Running)
+    }
+}
+
+impl Display for JobState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Running => f.write_str("running"),
+            Self::Finished { success: true, .. } => f.write_str("succeeded"),
+            Self::Finished {
+                exit_code: Some(code),
+                ..
+            } => write!(f, "failed (exit code {code})"),
+            Self::Finished { .. } => f.write_str("failed"),
+            Self::Cancelled => f.write_str("cancelled"),
+            Self::TimedOut => f.write_str("timed out"),
+            Self::Failed(error) => write!(f, "error: {error}"),
+        }
+    }
+}
+
+/// Status shared between a job's waiter thread and the tools
+#[derive(Debug, Default)]
+struct JobProgress {
+    state: JobState,
+    /// The formatted response, available once the job is no longer running
+    result: Option<String>,
+    finished_at: Option<Instant>,
+    cancel_requested: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    progress: Mutex<JobProgress>,
+    done: Condvar,
+}
+
+/// A cargo command running in the background
+#[derive(Debug)]
+pub struct Job {
+    id: u64,
+    command_name: String,
+    command: String,
+    project_path: PathBuf,
+    started_at: Instant,
+    child: Arc<Mutex<Child>>,
+    log: Arc<Mutex<OutputLog>>,
+    shared: Arc<Shared>,
+}
+
+/// A point-in-time view of a job
+#[derive(Debug, Clone)]
+pub struct JobSnapshot {
+    pub id: u64,
+    pub command_name: String,
+    pub command: String,
+    pub project_path: PathBuf,
+    pub state: JobState,
+    pub elapsed: Duration,
+    pub line_count: usize,
+}
+
+impl Display for JobSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Job {}: {} — {} after {:.1}s, {} lines of output\n  📁 {}\n  🔧 {}",
+            self.id,
+            self.command_name,
+            self.state,
+            self.elapsed.as_secs_f64(),
+            self.line_count,
+            self.project_path.display(),
+            self.command
+        )
+    }
+}
+
+impl Job {
+    fn with_progress<T>(&self, f: impl FnOnce(&mut JobProgress) -> T) -> T {
+        f(&mut self.shared.progress.lock().unwrap())
+    }
+
+    /// Current status of the job
+    pub fn snapshot(&self) -> JobSnapshot {
+        let line_count = self.log.lock().unwrap().len();
+        self.with_progress(|progress| JobSnapshot {
+            id: self.id,
+            command_name: self.command_name.clone(),
+            command: self.command.clone(),
+            project_path: self.project_path.clone(),
+            state: progress.state.clone(),
+            elapsed: progress
+                .finished_at
+                .unwrap_or_else(Instant::now)
+                .duration_since(self.started_at),
+            line_count,
+        })
+    }
+
+    /// Output lines `[start, end)` as a terminal would have shown them, optionally prefixed
+    /// with their arrival time
+    pub fn lines(&self, start: usize, end: usize, timestamps: bool) -> Vec<String> {
+        let log = self.log.lock().unwrap();
+        display_lines(
+            log.range(start, end).into_iter().map(|(_, line)| line),
+            timestamps,
+        )
+    }
+
+    /// Block until the job stops running or `timeout` elapses, returning the formatted result
+    /// if it finished
+    pub fn wait(&self, timeout: Duration) -> Option<String> {
+        let deadline = Instant::now() + timeout;
+        let mut progress = self.shared.progress.lock().unwrap();
+        loop {
+            if !progress.state.is_running() {
+                return progress.result.clone();
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            progress = self
+                .shared
+                .done
+                .wait_timeout(progress, remaining)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Kill the job's process if it is still running
+    pub fn cancel(&self) -> Result<()> {
+        let running = self.with_progress(|progress| {
+            if progress.state.is_running() {
+                progress.cancel_requested = true;
+            }
+            progress.state.is_running()
+        });
+
+        if running {
+            kill_process_tree(&mut self.child.lock().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+/// How many jobs to keep; the oldest finished jobs are forgotten beyond this
+const MAX_JOBS: usize = 200;
+
+/// Background jobs started during this server's lifetime
+#[derive(Debug, Default)]
+pub struct JobRegistry {
+    next_id: u64,
+    jobs: BTreeMap<u64, Job>,
+}
+
+impl JobRegistry {
+    /// Spawn a command in the background, returning its job ID
+    pub fn spawn(
+        &mut self,
+        mut cmd: Command,
+        project_path: &Path,
+        command_name: &str,
+        options: RunOptions,
+        runs: RunHistory,
+    ) -> Result<u64> {
+        cmd.current_dir(project_path);
+        isolate_process_group(&mut cmd);
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+        let capture = Capture::start(&mut child, None);
+
+        self.next_id += 1;
+        let id = self.next_id;
+
+        let job = Job {
+            id,
+            command_name: command_name.to_string(),
+            command: format_command(&cmd),
+            project_path: project_path.to_path_buf(),
+            started_at: Instant::now(),
+            child: Arc::new(Mutex::new(child)),
+            log: capture.log(),
+            shared: Arc::new(Shared::default()),
+        };
+
+        spawn_waiter(&job, capture, options, runs);
+
+        log::info!("started job {id}: {}", job.command);
+        self.jobs.insert(id, job);
+        self.prune();
+        Ok(id)
+    }
+
+    /// Forget the oldest finished jobs once there are more than MAX_JOBS. Their output stays
+    /// available through the run history.
+    fn prune(&mut self) {
+        let excess = self.jobs.len().saturating_sub(MAX_JOBS);
+        let expired = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| !job.with_progress(|progress| progress.state.is_running()))
+            .map(|(id, _)| *id)
+            .take(excess)
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.jobs.remove(&id);
+        }
+    }
+
+    /// Look up a job by ID
+    pub fn get(&self, id: u64) -> Result<&Job> {
+        self.jobs
+            .get(&id)
+            .ok_or_else(|| anyhow!("No job with id {id} (only the last {MAX_JOBS} jobs are kept)"))
+    }
+
+    /// All jobs, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values()
+    }
+}
+
+impl Drop for JobRegistry {
+    fn drop(&mut self) {
+        for job in self.jobs.values() {
+            let _ = job.cancel();
+        }
+    }
+}
+
+fn spawn_waiter(job: &Job, capture: Capture, options: RunOptions, runs: RunHistory) {
+    let timeout = options.timeout_secs.map(Duration::from_secs);
+    let child = Arc::clone(&job.child);
+    let shared = Arc::clone(&job.shared);
+    let command_name = job.command_name.clone();
+    let command = job.command.clone();
+    let project_path = job.project_path.clone();
+    let started_at = job.started_at;
+
+    thread::spawn(move || {
+        let mut timed_out = None;
+        let status: Result<ExitStatus, String> = loop {
+            // Only hold the lock for try_wait so job_cancel can reach the child
+            let polled = child.lock().unwrap().try_wait();
+            match polled {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => {
+                    if let Some(timeout) = timeout
+                        && started_at.elapsed() > timeout
+                    {
+                        timed_out = Some(timeout.as_secs());
+                        let _ = kill_process_tree(&mut child.lock().unwrap());
+                    }
+                    thread::sleep(Duration::from_millis(100));
+                }
+                Err(e) => break Err(e.to_string()),
+            }
+        };
+
+        let finished_at = Instant::now();
+        let cancel_requested = shared.progress.lock().unwrap().cancel_requested;
+        let state = match &status {
+            Err(error) => JobState::Failed(error.clone()),
+            Ok(_) if cancel_requested => JobState::Cancelled,
+            Ok(_) if timed_out.is_some() => JobState::TimedOut,
+            Ok(status) => JobState::Finished {
+                exit_code: status.code(),
+                success: status.success(),
+            },
+        };
+
+        // Collecting the output waits for the reader threads, which can take a while if
+        // something the command started still holds its stdout open, so do it before taking
+        // the lock job_status and job_wait need
+        let result = match status {
+            Ok(status) => {
+                let mut output = capture.finish(command, status, timed_out);
+                output.cancelled = matches!(state, JobState::Cancelled);
+                let full_output = options.record(&runs, &project_path, &output);
+                if output.cancelled {
+                    format!(
+                        "=== {command_name} ===\n❌ Job cancelled\n\nUse job_output or \
+                         {full_output} to see the output it produced.\n"
+                    )
+                } else {
+                    let rendered = options.format.render(&output, &command_name, &project_path);
+                    fit_run_output(&rendered, options.limits, &full_output, &output)
+                }
+            }
+            Err(_) => format!(
+                "=== {command_name} ===\n❌ Job {state}\n\nUse job_output to see the output it \
+                 produced.\n"
+            ),
+        };
+
+        let mut progress = shared.progress.lock().unwrap();
+        progress.finished_at = Some(finished_at);
+        progress.state = state;
+        progress.result = Some(result);
+        shared.done.notify_all();
+    });
+}
@@ -1,3 +1,8 @@
+mod jobs;
+mod progress;
+mod project;
+mod runs;
+mod server;
 mod state;
 mod tools;
 
@@ -12,6 +17,9 @@ const INSTRUCTIONS: &str = "Cargo operations for Rust projects.
 
 Use set_working_directory to set the project directory first, then run cargo commands.
 
+Long-running compile, test and run tools accept `background: true`, which returns a job ID
+immediately. Follow the job with job_status, job_output, job_wait and job_cancel.
+
 For embedded projects using espflash for flashing/monitoring, commands are
 automatically wrapped with `script` to provide a PTY required by espflash's
 terminal handling (crossterm requires a TTY for monitor mode).";
@@ -19,5 +27,9 @@ terminal handling (crossterm requires a TTY for monitor mode).";
 fn main() -> Result<()> {
     let mut state = CargoTools::new()?;
 
-    mcplease::run::<tools::Tools, _>(&mut state, server_info!(), Some(INSTRUCTIONS))
+    if std::env::args().nth(1).as_deref() == Some("serve") {
+        server::serve(&mut state, server_info!(), Some(INSTRUCTIONS))
+    } else {
+        mcplease::run::<tools::Tools, _>(&mut state, server_info!(), Some(INSTRUCTIONS))
+    }
 }
@@ -0,0 +1,115 @@
+use crate::server::send_notification;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+/// Minimum time between progress notifications, so a fast test suite doesn't flood the client
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Follows a running cargo command's output and reports how far along it is with MCP
+/// `notifications/progress` for the request that started it.
+///
+/// Build progress comes from cargo's `Building [===>  ] 12/40: foo, bar` status line, which
+/// cargo only prints when `CARGO_TERM_PROGRESS_WHEN=always` is set because output is piped.
+/// Test and bench progress is counted from libtest's `running N tests` and `test name ... ok`
+/// lines. Reported progress is the sum of both, so it only ever increases.
+#[derive(Debug)]
+pub struct ProgressTracker {
+    token: Value,
+    units_done: u64,
+    units_total: u64,
+    tests_done: u64,
+    tests_total: u64,
+    message: String,
+    last_sent: Option<Instant>,
+    last_progress: u64,
+}
+
+impl ProgressTracker {
+    pub fn new(token: Value) -> Self {
+        Self {
+            token,
+            units_done: 0,
+            units_total: 0,
+            tests_done: 0,
+            tests_total: 0,
+            message: String::new(),
+            last_sent: None,
+            last_progress: 0,
+        }
+    }
+
+    /// Update progress from a line of output, notifying the client if it moved
+    pub fn observe(&mut self, line: &str) {
+        if !self.parse(line.trim()) {
+            return;
+        }
+
+        let progress = self.units_done + self.tests_done;
+        let total = self.units_total + self.tests_total;
+        let finished = total > 0 && progress >= total;
+        let throttled = self
+            .last_sent
+            .is_some_and(|sent| sent.elapsed() < MIN_INTERVAL);
+        if progress <= self.last_progress || (throttled && !finished) {
+            return;
+        }
+
+        let mut params = json!({
+            "progressToken": self.token,
+            "progress": progress,
+            "message": self.message,
+        });
+        if total > 0 {
+            params["total"] = total.into();
+        }
+
+        if let Err(e) = send_notification("notifications/progress", params) {
+            log::warn!("failed to send progress notification: {e}");
+        }
+        self.last_sent = Some(Instant::now());
+        self.last_progress = progress;
+    }
+
+    /// Returns whether the line changed progress
+    fn parse(&mut self, line: &str) -> bool {
+        // "Building [=====>     ] 12/40: foo, bar(build)"
+        if let Some(status) = line.strip_prefix("Building [")
+            && let Some((_, status)) = status.split_once("] ")
+        {
+            let (counts, crates) = status.split_once(": ").unwrap_or((status, ""));
+            let Some((done, total)) = counts.split_once('/') else {
+                return false;
+            };
+            let (Ok(done), Ok(total)) = (done.trim().parse(), total.trim().parse()) else {
+                return false;
+            };
+            self.units_done = self.units_done.max(done);
+            self.units_total = self.units_total.max(total);
+            self.message = format!("Building {done}/{total}: {crates}");
+            return true;
+        }
+
+        // "running 12 tests"
+        if let Some(count) = line
+            .strip_prefix("running ")
+            .and_then(|rest| rest.strip_suffix(" tests").or(rest.strip_suffix(" test")))
+            .and_then(|count| count.parse::<u64>().ok())
+        {
+            self.tests_total += count;
+            self.message = format!("Running {count} tests");
+            return true;
+        }
+
+        // "test tests::it_works ... ok", including benchmarks' "... bench: 1,234 ns/iter"
+        if let Some((name, _)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.split_once(" ... "))
+        {
+            self.tests_done += 1;
+            self.message = format!("Tested {} of {}: {name}", self.tests_done, self.tests_total);
+            return true;
+        }
+
+        false
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{Result, anyhow};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Expand `~` and resolve a user-supplied path to an existing, absolute directory
+pub fn resolve_directory(path: &str) -> Result<PathBuf> {
+    let expanded = PathBuf::from(&*shellexpand::tilde(path));
+    let canonical = expanded
+        .canonicalize()
+        .map_err(|e| anyhow!("Cannot access {}: {e}", expanded.display()))?;
+
+    if canonical.is_dir() {
+        Ok(canonical)
+    } else {
+        // A path to Cargo.toml or a source file selects the directory containing it
+        canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| anyhow!("{} is not a directory", canonical.display()))
+    }
+}
+
+/// Walk up from `start` to the nearest directory containing a Cargo.toml
+pub fn find_package_root(start: &Path) -> Result<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+        .ok_or_else(|| {
+            anyhow!(
+                "Not a Rust project: no Cargo.toml found in {} or any parent directory",
+                start.display()
+            )
+        })
+}
+
+/// Walk up from a package root to the root of the workspace containing it, which is the
+/// package root itself for packages that are not part of a larger workspace
+pub fn find_workspace_root(package_root: &Path) -> PathBuf {
+    package_root
+        .ancestors()
+        .find(|dir| is_workspace_manifest(&dir.join("Cargo.toml")))
+        .unwrap_or(package_root)
+        .to_path_buf()
+}
+
+fn is_workspace_manifest(manifest: &Path) -> bool {
+    fs::read_to_string(manifest).is_ok_and(|contents| {
+        contents
+            .lines()
+            .any(|line| line.trim_start().starts_with("[workspace]"))
+    })
+}
+
+/// Describe a selected project: its package root and, if different, its workspace root
+pub fn describe_project(package_root: &Path) -> String {
+    let mut description = format!("📁 Working directory: {}\n", package_root.display());
+    let workspace_root = find_workspace_root(package_root);
+    if workspace_root != package_root {
+        description.push_str(&format!(
+            "🗂️  Workspace root: {}\n",
+            workspace_root.display()
+        ));
+    }
+    description
+}
@@ -0,0 +1,252 @@
+use crate::tools::capture::{display_lines, strip_timestamp};
+use crate::tools::cargo_utils::CargoOutput;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How many runs are kept before the oldest, and their logs, are deleted
+const MAX_RUNS: usize = 200;
+
+/// The tool call that started a command
+#[derive(Debug, Clone, Default)]
+pub struct Invocation {
+    /// Tool name, e.g. "cargo_test"
+    pub tool: String,
+    /// The tool's arguments as they were given
+    pub arguments: serde_json::Value,
+}
+
+impl Invocation {
+    pub fn new(tool: &str, arguments: &impl Serialize) -> Self {
+        Self {
+            tool: tool.to_string(),
+            arguments: serde_json::to_value(arguments).unwrap_or_default(),
+        }
+    }
+}
+
+/// How a recorded run ended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Succeeded,
+    Failed,
+    TimedOut,
+    Cancelled,
+}
+
+/// A finished cargo command, as stored in the run history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: u64,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub command: String,
+    pub cwd: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub outcome: RunOutcome,
+    pub exit_code: Option<i32>,
+    /// Number of lines in the log file
+    pub line_count: usize,
+    pub log_file: PathBuf,
+}
+
+impl Display for RunRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let outcome = match (self.outcome, self.exit_code) {
+            (RunOutcome::Succeeded, _) => "succeeded".to_string(),
+            (RunOutcome::Failed, Some(code)) => format!("failed (exit code {code})"),
+            (RunOutcome::Failed, None) => "failed".to_string(),
+            (RunOutcome::TimedOut, _) => "timed out".to_string(),
+            (RunOutcome::Cancelled, _) => "cancelled".to_string(),
+        };
+        write!(
+            f,
+            "Run {}: {} — {outcome} after {:.1}s, {}, {} lines of output\n  📁 {}\n  🔧 {}",
+            self.id,
+            self.tool,
+            self.duration_ms as f64 / 1000.0,
+            format_age(self.started_at),
+            self.line_count,
+            self.cwd.display(),
+            self.command
+        )?;
+        if let Some(toolchain) = &self.toolchain {
+            write!(f, "\n  🦀 Toolchain: {toolchain}")?;
+        }
+        if self
+            .arguments
+            .as_object()
+            .is_some_and(|arguments| !arguments.is_empty())
+        {
+            write!(f, "\n  🧾 Arguments: {}", self.arguments)?;
+        }
+        write!(f, "\n  📄 Log: {}", self.log_file.display())
+    }
+}
+
+impl RunRecord {
+    /// The run's log, one entry per line. Lines start with their arrival time and stream
+    /// unless `timestamps` is false.
+    pub fn read_log(&self, timestamps: bool) -> Result<Vec<String>> {
+        let file = File::open(&self.log_file)
+            .map_err(|e| anyhow!("Cannot read log {}: {e}", self.log_file.display()))?;
+        BufReader::new(file)
+            .lines()
+            .map(|line| {
+                let line = line?;
+                Ok(if timestamps {
+                    line
+                } else {
+                    strip_timestamp(&line).to_string()
+                })
+            })
+            .collect()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn format_age(started_at: u64) -> String {
+    let seconds = unix_now().saturating_sub(started_at);
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Every cargo command run through the server, persisted as a JSON-lines index plus one log
+/// file per run so that earlier output survives restarts. Cloned handles share one lock, so
+/// background jobs can record their runs from their own threads.
+#[derive(Debug, Clone)]
+pub struct RunHistory {
+    dir: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl RunHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            lock: Arc::default(),
+        }
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("runs.jsonl")
+    }
+
+    fn read_index(&self) -> Result<Vec<RunRecord>> {
+        let file = match File::open(self.index_path()) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        // Skip lines that don't parse rather than losing the whole history to one bad write
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    /// Store a finished command's log and metadata, returning the run ID
+    pub fn record(
+        &self,
+        invocation: &Invocation,
+        toolchain: Option<&str>,
+        project_path: &Path,
+        output: &CargoOutput,
+    ) -> Result<u64> {
+        let _guard = self.lock.lock().unwrap();
+        fs::create_dir_all(&self.dir)?;
+
+        let mut runs = self.read_index()?;
+        let id = runs.last().map_or(1, |run| run.id + 1);
+
+        let log = display_lines(&output.lines, true);
+        let log_file = self.dir.join(format!("{id}.log"));
+        let mut contents = log.join("\n");
+        contents.push('\n');
+        fs::write(&log_file, contents)?;
+
+        let outcome = if output.cancelled {
+            RunOutcome::Cancelled
+        } else if output.timed_out.is_some() {
+            RunOutcome::TimedOut
+        } else if output.status.success() {
+            RunOutcome::Succeeded
+        } else {
+            RunOutcome::Failed
+        };
+
+        let record = RunRecord {
+            id,
+            tool: invocation.tool.clone(),
+            arguments: invocation.arguments.clone(),
+            command: output.command.clone(),
+            cwd: project_path.to_path_buf(),
+            toolchain: toolchain.map(String::from),
+            started_at: unix_now().saturating_sub(output.duration.as_secs()),
+            duration_ms: output.duration.as_millis() as u64,
+            outcome,
+            exit_code: output.status.code(),
+            line_count: log.len(),
+            log_file,
+        };
+        runs.push(record);
+
+        if runs.len() > MAX_RUNS {
+            let expired = runs.drain(..runs.len() - MAX_RUNS).collect::<Vec<_>>();
+            for run in expired {
+                let _ = fs::remove_file(&run.log_file);
+            }
+            let mut index = String::new();
+            for run in &runs {
+                index.push_str(&serde_json::to_string(run)?);
+                index.push('\n');
+            }
+            fs::write(self.index_path(), index)?;
+        } else {
+            let mut index = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.index_path())?;
+            writeln!(index, "{}", serde_json::to_string(&runs[runs.len() - 1])?)?;
+        }
+
+        Ok(id)
+    }
+
+    /// Recorded runs, oldest first
+    pub fn list(&self) -> Result<Vec<RunRecord>> {
+        let _guard = self.lock.lock().unwrap();
+        self.read_index()
+    }
+
+    /// Look up a run by ID
+    pub fn get(&self, id: u64) -> Result<RunRecord> {
+        self.list()?
+            .into_iter()
+            .find(|run| run.id == id)
+            .ok_or_else(|| anyhow!("No run with id {id} (only the last {MAX_RUNS} runs are kept)"))
+    }
+}
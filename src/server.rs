@@ -0,0 +1,170 @@
+use crate::state::CargoTools;
+use crate::tools::Tools;
+use anyhow::Result;
+use env_logger::{Builder, Target};
+use mcplease::types::{Info, McpMessage, McpRequest};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+};
+
+/// Write one JSON-RPC message to stdout. Messages may come from several threads (responses from
+/// the main loop, progress notifications from output readers), so each is written whole under
+/// the stdout lock.
+pub fn send_message(message: &impl Serialize) -> Result<()> {
+    let message = serde_json::to_string(message)?;
+    log::trace!("-> {message}");
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(message.as_bytes())?;
+    stdout.write_all(b"\n")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Send a JSON-RPC notification to the client
+pub fn send_notification(method: &str, params: Value) -> Result<()> {
+    send_message(&json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    }))
+}
+
+/// The progress token a client attached to a request, if any
+fn progress_token(params: Option<&Value>) -> Option<Value> {
+    params?
+        .get("_meta")?
+        .get("progressToken")
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
+
+fn init_logging() {
+    let Ok(log_location) = std::env::var("MCP_LOG_LOCATION") else {
+        return;
+    };
+
+    let path = PathBuf::from(&*shellexpand::tilde(&log_location));
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => Builder::from_default_env()
+            .target(Target::Pipe(Box::new(file)))
+            .init(),
+        Err(e) => eprintln!("Cannot open {}: {e}", path.display()),
+    }
+}
+
+/// Set when the client cancels the request being served, so the command it started can be
+/// killed
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Cancellation tokens for requests that have been read but not yet answered, keyed by the
+/// request ID's JSON
+type InFlight = Arc<Mutex<HashMap<String, CancellationToken>>>;
+
+/// Read messages from stdin on their own thread, so that a `notifications/cancelled` can reach
+/// a request while the main thread is busy serving it. Requests are passed on to the main
+/// thread in order.
+fn read_messages(requests: Sender<(McpRequest, CancellationToken)>, in_flight: InFlight) {
+    let mut reader = BufReader::new(std::io::stdin());
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => break, // EOF
+            Ok(_) => {
+                log::trace!("<- {line}");
+                match serde_json::from_str(&line) {
+                    Ok(McpMessage::Request(request)) => {
+                        let cancellation = CancellationToken::default();
+                        in_flight
+                            .lock()
+                            .unwrap()
+                            .insert(request.id.to_string(), cancellation.clone());
+                        if requests.send((request, cancellation)).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(McpMessage::Notification(n)) if n.method == "notifications/cancelled" => {
+                        let request_id =
+                            n.params.as_ref().and_then(|params| params.get("requestId"));
+                        log::info!("client cancelled request {request_id:?}");
+                        if let Some(request_id) = request_id
+                            && let Some(cancellation) =
+                                in_flight.lock().unwrap().get(&request_id.to_string())
+                        {
+                            cancellation.cancel();
+                        }
+                    }
+                    Ok(McpMessage::Notification(n)) => {
+                        log::trace!("received {n:?}, ignoring");
+                    }
+                    Err(e) => {
+                        log::error!("{e:?}");
+                    }
+                }
+            }
+            Err(e) => {
+                log::error!("Error reading line: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// Serve MCP over stdio. This follows mcplease's serve loop, but also gives tools the request's
+/// progress token so long cargo commands can report progress while they run, and a cancellation
+/// token that kills them if the client cancels the request.
+pub fn serve(
+    state: &mut CargoTools,
+    server_info: Info,
+    instructions: Option<&'static str>,
+) -> Result<()> {
+    init_logging();
+
+    log::trace!("started!");
+
+    let in_flight = InFlight::default();
+    let (sender, requests) = mpsc::channel();
+    thread::spawn({
+        let in_flight = Arc::clone(&in_flight);
+        move || read_messages(sender, in_flight)
+    });
+
+    for (request, cancellation) in requests {
+        let id = request.id.to_string();
+        state.set_progress_token(progress_token(request.params.as_ref()));
+        state.set_cancellation(Some(cancellation));
+        let response = request.execute::<CargoTools, Tools>(state, instructions, &server_info);
+        state.set_progress_token(None);
+        state.set_cancellation(None);
+        in_flight.lock().unwrap().remove(&id);
+        send_message(&response)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
+use crate::jobs::JobRegistry;
+use crate::project::{find_package_root, resolve_directory};
+use crate::runs::RunHistory;
+use crate::server::CancellationToken;
+use crate::tools::cargo_utils::{DEFAULT_MAX_OUTPUT_BYTES, OutputLimits};
 use anyhow::{Result, anyhow};
 use fieldwork::Fieldwork;
 use mcplease::session::SessionStore;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
     fmt::{self, Debug, Formatter},
     path::PathBuf,
@@ -12,6 +18,18 @@ use std::{
 pub struct CargoSessionData {
     /// Default toolchain to use for cargo commands (e.g., "stable", "nightly", "1.70.0")
     default_toolchain: Option<String>,
+
+    /// Default timeout in seconds for cargo commands
+    default_timeout: Option<u64>,
+
+    /// Default response size limit in bytes, 0 for no limit
+    default_max_output_bytes: Option<usize>,
+
+    /// Default response size limit in lines, 0 for no limit
+    default_max_lines: Option<usize>,
+
+    /// Project directory selected with set_working_directory
+    working_directory: Option<PathBuf>,
 }
 
 /// Cargo tools with session support
@@ -20,12 +38,30 @@ pub struct CargoSessionData {
 pub struct CargoTools {
     /// Private session store for cargo-specific state
     session_store: SessionStore<CargoSessionData>,
+
+    /// Background jobs started by this server
+    jobs: JobRegistry,
+
+    /// Persistent record of every cargo command run, with its full log
+    runs: RunHistory,
+
+    /// Progress token of the MCP request being served, if the client asked for progress
+    #[fieldwork(set)]
+    progress_token: Option<Value>,
+
+    /// Cancellation token of the MCP request being served
+    #[fieldwork(set)]
+    cancellation: Option<CancellationToken>,
 }
 
 impl Debug for CargoTools {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("CargoTools")
             .field("session_store", &self.session_store)
+            .field("jobs", &self.jobs)
+            .field("runs", &self.runs)
+            .field("progress_token", &self.progress_token)
+            .field("cancellation", &self.cancellation)
             .finish()
     }
 }
@@ -38,9 +74,18 @@ impl CargoTools {
         private_path.push(".ai-tools");
         private_path.push("sessions");
         private_path.push("cargo-mcp.json");
+
+        // Run history lives alongside the session file
+        let runs = RunHistory::new(private_path.with_file_name("cargo-mcp-runs"));
         let session_store = SessionStore::new(Some(private_path))?;
 
-        let mut tools = Self { session_store };
+        let mut tools = Self {
+            session_store,
+            jobs: JobRegistry::default(),
+            runs,
+            progress_token: None,
+            cancellation: None,
+        };
 
         // Check for default toolchain from environment variable
         if let Ok(toolchain) = std::env::var("CARGO_MCP_DEFAULT_TOOLCHAIN")
@@ -50,6 +95,39 @@ impl CargoTools {
             tools.set_default_toolchain(Some(toolchain))?;
         }
 
+        // Check for default timeout from environment variable
+        if let Ok(timeout) = std::env::var("CARGO_MCP_DEFAULT_TIMEOUT")
+            && !timeout.is_empty()
+        {
+            let timeout = timeout
+                .parse()
+                .map_err(|e| anyhow!("Invalid CARGO_MCP_DEFAULT_TIMEOUT {timeout:?}: {e}"))?;
+            log::info!("Setting default timeout from CARGO_MCP_DEFAULT_TIMEOUT: {timeout}s");
+            tools.set_default_timeout(Some(timeout))?;
+        }
+
+        // Check for default response limits from environment variables
+        if let Ok(max_bytes) = std::env::var("CARGO_MCP_MAX_OUTPUT_BYTES")
+            && !max_bytes.is_empty()
+        {
+            let max_bytes = max_bytes
+                .parse()
+                .map_err(|e| anyhow!("Invalid CARGO_MCP_MAX_OUTPUT_BYTES {max_bytes:?}: {e}"))?;
+            log::info!(
+                "Setting default response limit from CARGO_MCP_MAX_OUTPUT_BYTES: {max_bytes}"
+            );
+            tools.set_default_max_output_bytes(Some(max_bytes))?;
+        }
+        if let Ok(max_lines) = std::env::var("CARGO_MCP_MAX_LINES")
+            && !max_lines.is_empty()
+        {
+            let max_lines = max_lines
+                .parse()
+                .map_err(|e| anyhow!("Invalid CARGO_MCP_MAX_LINES {max_lines:?}: {e}"))?;
+            log::info!("Setting default line limit from CARGO_MCP_MAX_LINES: {max_lines}");
+            tools.set_default_max_lines(Some(max_lines))?;
+        }
+
         Ok(tools)
     }
 
@@ -66,19 +144,97 @@ impl CargoTools {
         })
     }
 
-    /// Check if the current working directory is a Rust project
-    pub fn ensure_rust_project(&mut self) -> Result<PathBuf> {
-        let context = std::env::current_dir()
-            .map_err(|e| anyhow!("Failed to get current working directory: {}", e))?;
-
-        let cargo_toml = context.join("Cargo.toml");
-        if !cargo_toml.exists() {
-            return Err(anyhow!(
-                "Not a Rust project: Cargo.toml not found in {}",
-                context.display()
-            ));
+    /// Get the default timeout in seconds
+    pub fn get_default_timeout(&mut self) -> Result<Option<u64>> {
+        let session_data = self.session_store.get_or_create("default")?;
+        Ok(session_data.default_timeout)
+    }
+
+    /// Set the default timeout in seconds
+    pub fn set_default_timeout(&mut self, timeout: Option<u64>) -> Result<()> {
+        self.session_store.update("default", |data| {
+            data.default_timeout = timeout;
+        })
+    }
+
+    /// Get the default response limit in bytes
+    pub fn get_default_max_output_bytes(&mut self) -> Result<Option<usize>> {
+        let session_data = self.session_store.get_or_create("default")?;
+        Ok(session_data.default_max_output_bytes)
+    }
+
+    /// Set the default response limit in bytes
+    pub fn set_default_max_output_bytes(&mut self, max_bytes: Option<usize>) -> Result<()> {
+        self.session_store.update("default", |data| {
+            data.default_max_output_bytes = max_bytes;
+        })
+    }
+
+    /// Get the default response limit in lines
+    pub fn get_default_max_lines(&mut self) -> Result<Option<usize>> {
+        let session_data = self.session_store.get_or_create("default")?;
+        Ok(session_data.default_max_lines)
+    }
+
+    /// Set the default response limit in lines
+    pub fn set_default_max_lines(&mut self, max_lines: Option<usize>) -> Result<()> {
+        self.session_store.update("default", |data| {
+            data.default_max_lines = max_lines;
+        })
+    }
+
+    /// Resolve response limits from args, session defaults, or the built-in byte budget. A
+    /// limit of 0 disables it.
+    pub fn output_limits(
+        &mut self,
+        max_bytes: Option<usize>,
+        max_lines: Option<usize>,
+    ) -> OutputLimits {
+        let max_bytes = max_bytes
+            .or_else(|| self.get_default_max_output_bytes().unwrap_or(None))
+            .unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+        let max_lines = max_lines.or_else(|| self.get_default_max_lines().unwrap_or(None));
+        OutputLimits {
+            max_bytes: (max_bytes > 0).then_some(max_bytes),
+            max_lines: max_lines.filter(|&max| max > 0),
         }
+    }
+
+    /// Get the project directory selected with set_working_directory
+    pub fn get_working_directory(&mut self) -> Result<Option<PathBuf>> {
+        let session_data = self.session_store.get_or_create("default")?;
+        Ok(session_data.working_directory.clone())
+    }
+
+    /// Select the project that tools operate on, returning the package root found by walking
+    /// up from `path`
+    pub fn set_working_directory(&mut self, path: &str) -> Result<PathBuf> {
+        let package_root = find_package_root(&resolve_directory(path)?)?;
+        let working_directory = package_root.clone();
+        self.session_store.update("default", |data| {
+            data.working_directory = Some(working_directory);
+        })?;
+        Ok(package_root)
+    }
+
+    /// Resolve the project a tool operates on: the package containing `path` if one was given,
+    /// otherwise the selected project
+    pub fn resolve_project(&mut self, path: Option<&str>) -> Result<PathBuf> {
+        match path {
+            Some(path) => find_package_root(&resolve_directory(path)?),
+            None => self.ensure_rust_project(),
+        }
+    }
+
+    /// Resolve the project to operate on: the directory selected with set_working_directory,
+    /// or the nearest Cargo project containing the process's current directory
+    pub fn ensure_rust_project(&mut self) -> Result<PathBuf> {
+        let context = match self.get_working_directory()? {
+            Some(working_directory) => working_directory,
+            None => std::env::current_dir()
+                .map_err(|e| anyhow!("Failed to get current working directory: {}", e))?,
+        };
 
-        Ok(context)
+        find_package_root(&context)
     }
 }
@@ -5,3 +5,827 @@ use mcplease::traits::AsToolsList;
 fn tools_doesnt_panic() {
     Tools::tools_list();
 }
+
+#[test]
+fn parses_compiler_messages() {
+    use crate::tools::diagnostics::{parse_messages, summarize};
+
+    let stdout = r#"{"reason":"compiler-artifact","package_id":"path+file:///tmp/demo#0.1.0"}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","message":{"rendered":"","children":[{"children":[],"code":null,"level":"help","message":"if this is intentional, prefix it with an underscore","rendered":null,"spans":[{"file_name":"src/main.rs","is_primary":true,"label":null,"line_start":3,"column_start":9,"suggested_replacement":"_y"}]}],"level":"warning","message":"unused variable: `y`","spans":[{"file_name":"src/main.rs","is_primary":true,"label":null,"line_start":3,"column_start":9,"suggested_replacement":null}],"code":{"code":"unused_variables","explanation":null}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","message":{"rendered":"","children":[],"level":"error","message":"mismatched types","spans":[{"file_name":"src/main.rs","is_primary":true,"label":"expected `u32`, found `&str`","line_start":2,"column_start":18,"suggested_replacement":null},{"file_name":"src/main.rs","is_primary":false,"label":"expected due to this","line_start":2,"column_start":12,"suggested_replacement":null}],"code":{"code":"E0308","explanation":"..."}}}
+{"reason":"compiler-message","package_id":"path+file:///tmp/demo#0.1.0","message":{"rendered":"","children":[],"level":"error","message":"aborting due to 1 previous error","spans":[],"code":null}}
+{"reason":"build-finished","success":false}
+not json
+"#;
+
+    let (diagnostics, other) = parse_messages(stdout);
+    assert_eq!(other, "not json\n");
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(summarize(&diagnostics), "1 error, 1 warning");
+    let mut ice = diagnostics[1].clone();
+    ice.level = "error: internal compiler error".into();
+    let crashed = [diagnostics[0].clone(), ice.clone(), ice];
+    assert_eq!(summarize(&crashed), "2 internal compiler errors, 1 warning");
+
+    let warning = &diagnostics[0];
+    assert_eq!(warning.code.as_deref(), Some("unused_variables"));
+    assert_eq!(warning.children[0].suggestion.as_deref(), Some("_y"));
+
+    let error = &diagnostics[1];
+    let location = error.location.as_ref().unwrap();
+    assert_eq!(
+        (location.file.as_str(), location.line, location.column),
+        ("src/main.rs", 2, 18)
+    );
+    assert_eq!(error.labels.len(), 2);
+}
+
+#[test]
+fn counts_diagnostics_by_lint() {
+    use crate::tools::diagnostics::{count_by_lint, parse_messages};
+
+    let message = |level: &str, code: &str, line: usize| {
+        format!(
+            r#"{{"reason":"compiler-message","message":{{"rendered":"","children":[],"level":"{level}","message":"m","spans":[{{"file_name":"src/lib.rs","is_primary":true,"label":null,"line_start":{line},"column_start":1,"suggested_replacement":null}}],"code":{{"code":"{code}","explanation":null}}}}}}"#
+        )
+    };
+    let stdout = [
+        message("warning", "clippy::needless_return", 1),
+        message("warning", "dead_code", 2),
+        message("warning", "clippy::needless_return", 3),
+        message("error", "clippy::needless_return", 4),
+    ]
+    .join("\n");
+
+    let (diagnostics, _) = parse_messages(&stdout);
+    let counts = count_by_lint(&diagnostics)
+        .into_iter()
+        .map(|count| (count.lint, count.level, count.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        counts,
+        [
+            ("clippy::needless_return".into(), "warning".into(), 2),
+            ("clippy::needless_return".into(), "error".into(), 1),
+            ("dead_code".into(), "warning".into(), 1),
+        ]
+    );
+}
+
+#[test]
+fn accepts_features_as_string_or_list() {
+    use crate::tools::compile_options::CompileOptions;
+
+    let args = |json: &str| serde_json::from_str::<CompileOptions>(json).unwrap().args();
+    assert_eq!(args(r#"{"features": "a b,c"}"#), ["--features", "a,b,c"]);
+    assert_eq!(
+        args(r#"{"features": ["a", "b c"]}"#),
+        ["--features", "a,b,c"]
+    );
+    assert!(args(r#"{"features": ""}"#).is_empty());
+
+    // cargo_metadata and cargo_tree resolve features without compiling, and take them the same way
+    use crate::tools::{CargoMetadata, CargoTree};
+    let metadata = serde_json::from_str::<CargoMetadata>(r#"{"features": "a b,c"}"#).unwrap();
+    assert_eq!(metadata.features.unwrap().names(), ["a", "b", "c"]);
+    let tree = serde_json::from_str::<CargoTree>(r#"{"features": ["a", "b c"]}"#).unwrap();
+    assert_eq!(tree.features.unwrap().names(), ["a", "b", "c"]);
+}
+
+#[test]
+fn lists_feature_combinations() {
+    use crate::tools::feature_matrix::{Combination, combinations};
+
+    let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let features = strings(&["a", "b", "c", "default", "nightly"]);
+
+    let labels = |combinations: Option<Vec<Combination>>| {
+        combinations
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        labels(combinations(&features, &strings(&["nightly"]), &[], 1, 0)),
+        [
+            "default",
+            "no-default-features",
+            "a",
+            "b",
+            "c",
+            "all-features"
+        ]
+    );
+    assert_eq!(
+        labels(combinations(
+            &features,
+            &strings(&["nightly"]),
+            &[strings(&["a", "c"])],
+            2,
+            7
+        )),
+        [
+            "default",
+            "no-default-features",
+            "a",
+            "b",
+            "c",
+            "a + b",
+            "b + c"
+        ]
+    );
+    // Too many combinations are refused without listing them all
+    let nightly = strings(&["nightly"]);
+    assert!(combinations(&features, &nightly, &[], 2, 8).is_none());
+    let many = (0..40).map(|i| format!("f{i}")).collect::<Vec<_>>();
+    assert!(combinations(&many, &[], &[], 20, 100).is_none());
+
+    assert_eq!(
+        Combination::Features(strings(&["a", "b"])).args(),
+        ["--no-default-features", "--features", "a,b"]
+    );
+}
+
+#[test]
+fn matches_advisories_against_lockfile() {
+    use crate::tools::advisories::Advisory;
+    use crate::tools::lockfile::Lockfile;
+    use semver::Version;
+
+    let advisory = Advisory::parse(
+        r#"```toml
+[advisory]
+id = "RUSTSEC-2099-0001"
+package = "memchr"
+date = 2099-01-02
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 2.9.0"]
+unaffected = ["< 2.0.0"]
+```
+
+# Out of bounds read
+"#,
+    )
+    .unwrap();
+    assert_eq!(advisory.title, "Out of bounds read");
+    assert_eq!(advisory.date, "2099-01-02");
+    assert_eq!(advisory.severity(), Some((9.8, "critical")));
+    assert!(advisory.affects(&Version::new(2, 8, 0)));
+    assert!(!advisory.affects(&Version::new(2, 9, 1)));
+    assert!(!advisory.affects(&Version::new(1, 0, 0)));
+
+    let lockfile = Lockfile::parse(
+        r#"version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["regex", "memchr 1.0.0"]
+
+[[package]]
+name = "memchr"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "memchr"
+version = "2.8.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "regex"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["memchr 2.8.0"]
+"#,
+    )
+    .unwrap();
+    let path = lockfile
+        .dependency_path(2)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(path, ["app 0.1.0", "regex 1.0.0", "memchr 2.8.0"]);
+}
+
+#[test]
+fn evaluates_license_policy() {
+    use crate::tools::policy::{LicenseExpr, Policy};
+
+    let allow = vec!["MIT".to_string(), "Apache-2.0".to_string()];
+    let expression = LicenseExpr::parse("(MIT OR Apache-2.0) AND Unicode-3.0").unwrap();
+    assert!(!expression.allowed_by(&allow));
+    assert_eq!(expression.disallowed(&allow), ["Unicode-3.0"]);
+    assert!(
+        LicenseExpr::parse("MIT/Apache-2.0")
+            .unwrap()
+            .allowed_by(&allow)
+    );
+    assert!(
+        LicenseExpr::parse("Apache-2.0 WITH LLVM-exception OR GPL-3.0")
+            .unwrap()
+            .allowed_by(&allow)
+    );
+    assert!(LicenseExpr::parse("MIT AND (Apache-2.0").is_err());
+
+    let policy = Policy::parse(
+        r#"
+[licenses]
+allow = ["MIT"]
+
+[bans]
+multiple-versions = "deny"
+deny = ["openssl-sys", { name = "time", version = "<0.2" }]
+"#,
+    )
+    .unwrap();
+    assert_eq!(policy.licenses.unwrap().allow, ["MIT"]);
+    assert_eq!(policy.bans.deny.len(), 2);
+    assert_eq!(policy.sources.allow_registry.len(), 1);
+}
+
+#[test]
+fn diffs_lockfiles() {
+    use crate::tools::lockfile::{LockedPackage, Lockfile, VersionChange};
+
+    let lockfile = |packages: &[(&str, &str)]| Lockfile {
+        packages: packages
+            .iter()
+            .map(|(name, version)| LockedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                source: None,
+                dependencies: Vec::new(),
+            })
+            .collect(),
+    };
+    let before = lockfile(&[
+        ("a", "1.0.0"),
+        ("b", "1.2.0"),
+        ("c", "0.1.0"),
+        ("d", "1.0.0"),
+    ]);
+    let after = lockfile(&[
+        ("a", "1.0.0"),
+        ("b", "1.10.0"),
+        ("c", "0.0.9"),
+        ("e", "2.0.0"),
+    ]);
+    let diff = before.diff(&after);
+    let changes = |changes: &[VersionChange]| {
+        changes
+            .iter()
+            .map(|change| (change.name.clone(), change.from.clone(), change.to.clone()))
+            .collect::<Vec<_>>()
+    };
+    let change = |name: &str, from: Option<&str>, to: Option<&str>| {
+        vec![(name.into(), from.map(String::from), to.map(String::from))]
+    };
+    assert_eq!(changes(&diff.added), change("e", None, Some("2.0.0")));
+    assert_eq!(changes(&diff.removed), change("d", Some("1.0.0"), None));
+    assert_eq!(
+        changes(&diff.upgraded),
+        change("b", Some("1.2.0"), Some("1.10.0"))
+    );
+    assert_eq!(
+        changes(&diff.downgraded),
+        change("c", Some("0.1.0"), Some("0.0.9"))
+    );
+    assert!(before.diff(&before).is_empty());
+}
+
+#[test]
+fn reads_registry_index_cache() {
+    use crate::tools::registry_index::{index_path, parse_cache, parse_index_file};
+
+    assert_eq!(index_path("a"), "1/a");
+    assert_eq!(index_path("syn"), "3/s/syn");
+    assert_eq!(index_path("Serde_JSON"), "se/rd/serde_json");
+
+    let mut cache = vec![3, 2, 0, 0, 0];
+    cache.extend_from_slice(b"Unknown\0");
+    for (version, yanked) in [("1.0.0", false), ("1.1.0", true)] {
+        cache.extend_from_slice(version.as_bytes());
+        cache.push(0);
+        cache.extend_from_slice(
+            format!(r#"{{"name":"x","vers":"{version}","deps":[],"yanked":{yanked}}}"#).as_bytes(),
+        );
+        cache.push(0);
+    }
+    let entries = parse_cache(&cache);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].vers, "1.1.0");
+    assert!(entries[1].yanked);
+    assert!(parse_cache(&[1, 0]).is_empty());
+
+    let entries = parse_index_file("{\"name\":\"x\",\"vers\":\"0.1.0\"}\n\n");
+    assert_eq!(entries.len(), 1);
+    assert!(!entries[0].yanked);
+}
+
+#[test]
+fn promotes_workspace_dependencies() {
+    use crate::tools::workspace_deps::{Manifest, promote};
+
+    let manifest = |path: &str, member: Option<&str>, contents: &str| {
+        Manifest::parse(path.into(), member.map(str::to_string), contents.into()).unwrap()
+    };
+    let mut manifests = vec![
+        manifest(
+            "/ws/Cargo.toml",
+            None,
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        ),
+        manifest(
+            "/ws/a/Cargo.toml",
+            Some("a"),
+            "[dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] } # config\nb = { path = \"../b\" }\n",
+        ),
+        manifest(
+            "/ws/b/Cargo.toml",
+            Some("b"),
+            "[target.'cfg(unix)'.dependencies]\nserde = { version = \"1.0.150\", default-features = false }\n",
+        ),
+    ];
+
+    let promotion = promote(&mut manifests, 0, "serde", None).unwrap();
+    assert_eq!(promotion.entry.version.as_deref(), Some("1.0.150"));
+    assert_eq!(promotion.declarations.len(), 2);
+    promote(&mut manifests, 0, "b", None).unwrap();
+    assert!(promote(&mut manifests, 0, "missing", None).is_err());
+
+    assert_eq!(
+        manifests[0].document.to_string(),
+        "[workspace]\nmembers = [\"a\", \"b\"]\n\n[workspace.dependencies]\n\
+         serde = { version = \"1.0.150\", default-features = false }\nb = { path = \"b\" }\n"
+    );
+    assert_eq!(
+        manifests[1].document.to_string(),
+        "[dependencies]\nserde = { workspace = true, features = [\"derive\"], default-features = true } # config\n\
+         b = { workspace = true }\n"
+    );
+    assert_eq!(
+        manifests[2].document.to_string(),
+        "[target.'cfg(unix)'.dependencies]\nserde = { workspace = true }\n"
+    );
+
+    // rand 0.7 and 0.8 are different semver series, so neither can stand in for the other
+    let mut manifests = vec![
+        manifest(
+            "/ws/Cargo.toml",
+            None,
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        ),
+        manifest(
+            "/ws/a/Cargo.toml",
+            Some("a"),
+            "[dependencies]\nrand = \"0.7\"\n",
+        ),
+        manifest(
+            "/ws/b/Cargo.toml",
+            Some("b"),
+            "[dependencies]\nrand = \"0.8\"\n",
+        ),
+    ];
+    let error = promote(&mut manifests, 0, "rand", None).unwrap_err();
+    assert_eq!(error.to_string(), "rand 0.8 is incompatible with a (0.7)");
+    assert!(promote(&mut manifests, 0, "rand", Some("0.7.3")).is_err());
+    assert!(manifests.iter().all(|manifest| !manifest.changed()));
+
+    // A version given for an existing entry replaces only its version
+    let mut manifests = vec![
+        manifest(
+            "/ws/Cargo.toml",
+            None,
+            "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\n\
+             serde = { version = \"1.0\", features = [\"derive\"] } # pinned\n",
+        ),
+        manifest(
+            "/ws/a/Cargo.toml",
+            Some("a"),
+            "[dependencies]\nserde = \"1.0\"\n",
+        ),
+    ];
+    let promotion = promote(&mut manifests, 0, "serde", Some("1.0.200")).unwrap();
+    assert_eq!(
+        promotion.entry.describe(),
+        "{ version = \"1.0.200\", features = [\"derive\"] }"
+    );
+    assert_eq!(
+        manifests[0].document.to_string(),
+        "[workspace]\nmembers = [\"a\"]\n\n[workspace.dependencies]\n\
+         serde = { version = \"1.0.200\", features = [\"derive\"] } # pinned\n"
+    );
+
+    // A table declaration keeps its other keys and their comments, and keys an inherited
+    // dependency can't have stop the promotion
+    let mut manifests = vec![
+        manifest(
+            "/ws/Cargo.toml",
+            None,
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        ),
+        manifest(
+            "/ws/a/Cargo.toml",
+            Some("a"),
+            "[dependencies.anyhow]\nversion = \"1.0\"\nfeatures = [\"backtrace\"] # keep me\n\
+             optional = true\n\n[dependencies]\nbin = { version = \"1\", artifact = \"bin\" }\n",
+        ),
+    ];
+    promote(&mut manifests, 0, "anyhow", None).unwrap();
+    assert_eq!(
+        manifests[1].document.to_string(),
+        "[dependencies.anyhow]\nworkspace = true\nfeatures = [\"backtrace\"] # keep me\n\
+         optional = true\n\n[dependencies]\nbin = { version = \"1\", artifact = \"bin\" }\n"
+    );
+    let error = promote(&mut manifests, 0, "bin", None).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "a sets artifact on bin, which an inherited dependency can't"
+    );
+}
+
+#[test]
+fn splits_rustfmt_output() {
+    use crate::tools::rustfmt_output::{listed_files, split_emitted};
+    use std::path::Path;
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (main, tests) = (root.join("src/main.rs"), root.join("src/tests.rs"));
+    assert_eq!(
+        listed_files("src/main.rs\n  src/tests.rs  \nWarning: something\n", root),
+        [main.clone(), tests.clone()]
+    );
+
+    let stdout = format!(
+        "{}:\n\nfn a() {{\n    let x = 1;\n}}\n{}:\n\nfn b() {{}}\n\nfn c() {{}}\n",
+        main.display(),
+        tests.display()
+    );
+    let formatted = split_emitted(&stdout, &[main.clone(), tests.clone()]);
+    assert_eq!(formatted[&main], "fn a() {\n    let x = 1;\n}\n");
+    assert_eq!(formatted[&tests], "fn b() {}\n\nfn c() {}\n");
+
+    let formatted = split_emitted(&stdout, std::slice::from_ref(&tests));
+    assert_eq!(formatted.len(), 1);
+    assert!(formatted.contains_key(&tests));
+    // A child module passed along with its parent is printed twice, once before the parent
+    let stdout = format!(
+        "{tests}:\n\npub fn g() {{}}\n{main}:\n\nmod tests;\n{tests}:\n\npub fn g() {{}}\n",
+        main = main.display(),
+        tests = tests.display()
+    );
+    let formatted = split_emitted(&stdout, &[main.clone(), tests.clone()]);
+    assert_eq!(formatted[&main], "mod tests;\n");
+    assert_eq!(formatted[&tests], "pub fn g() {}\n");
+}
+
+#[test]
+fn parses_test_output() {
+    use crate::tools::test_results::{TestOutcome, parse_test_output};
+
+    let stderr = "     Running unittests src/lib.rs (target/debug/deps/demo-b37ab8512a90f0b3)
+     Running tests/it.rs (target/debug/deps/it-7318485afccc7ac4)
+";
+    let stdout = "
+running 3 tests
+test tests::bad ... FAILED <0.003s>
+test tests::ign ... ignored, slow
+test tests::ok ... ok <1.250s>
+
+failures:
+
+---- tests::bad stdout ----
+captured
+
+thread 'tests::bad' (5839) panicked at src/lib.rs:5:46:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::bad
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.02s
+
+
+running 1 test
+test integ ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+";
+
+    let binaries = parse_test_output(stdout, stderr);
+    assert_eq!(binaries.len(), 2);
+    assert_eq!(binaries[0].name, "unittests src/lib.rs");
+    assert_eq!(binaries[1].name, "tests/it.rs");
+
+    let bad = &binaries[0].tests[0];
+    assert_eq!(bad.outcome, TestOutcome::Failed);
+    assert_eq!(bad.duration.as_deref(), Some("0.003s"));
+    assert_eq!(bad.output.as_deref(), Some("captured"));
+    let panic = bad.panic.as_ref().unwrap();
+    assert_eq!(panic.location.as_deref(), Some("src/lib.rs:5:46"));
+    assert_eq!(
+        panic.message,
+        "assertion `left == right` failed\n  left: 1\n right: 2"
+    );
+
+    assert_eq!(binaries[0].tests[1].ignore_reason.as_deref(), Some("slow"));
+    assert_eq!(binaries[0].tests[1].duration, None);
+    assert_eq!(binaries[0].tests[2].outcome, TestOutcome::Passed);
+    assert_eq!(binaries[0].tests[2].duration.as_deref(), Some("1.250s"));
+    let summary = binaries[0].summary.as_ref().unwrap();
+    assert_eq!((summary.passed, summary.failed, summary.ignored), (1, 1, 1));
+    assert_eq!(summary.duration.as_deref(), Some("0.02s"));
+    assert_eq!(binaries[1].tests[0].outcome, TestOutcome::Passed);
+    assert_eq!(binaries[1].tests[0].duration, None);
+}
+
+#[test]
+fn fits_output_to_limits() {
+    use crate::tools::cargo_utils::{OutputLimits, fit_logged_output, fit_output};
+
+    let mut text = String::from("=== cargo build ===\n");
+    for index in 0..50 {
+        text.push_str(&format!("   Compiling dep{index} v1.0.{index}\n"));
+    }
+    for _ in 0..3 {
+        text.push_str("warning: unused variable: `x`\n --> src/main.rs:2:9\n\n");
+    }
+    text.push_str("    Finished `dev` profile\n");
+
+    let unlimited = OutputLimits::default();
+    assert_eq!(fit_output(&text, unlimited, "get_run_output"), text);
+
+    let limits = OutputLimits {
+        max_bytes: None,
+        max_lines: Some(10),
+    };
+    let fitted = fit_output(&text, limits, "get_run_output with run_id 1");
+    assert!(fitted.contains("   Compiling 50 crates (dep0 v1.0.0 … dep49 v1.0.49)\n"));
+    assert_eq!(fitted.matches("unused variable").count(), 1);
+    assert!(fitted.contains("collapsed 49 per-crate progress lines"));
+    assert!(fitted.contains("dropped 2 duplicate warnings"));
+    assert!(!fitted.contains("elided"));
+
+    let limits = OutputLimits {
+        max_bytes: Some(100),
+        max_lines: None,
+    };
+    let fitted = fit_output(&text, limits, "get_run_output with run_id 1");
+    assert!(fitted.starts_with("=== cargo build ===\n"));
+    assert!(fitted.contains("lines ("));
+    assert!(fitted.contains("elided here, use get_run_output with run_id 1"));
+    assert!(fitted.contains("    Finished `dev` profile\n"));
+
+    // With the run's log at hand, the marker says how long it is
+    let fitted = fit_logged_output(&text, limits, "get_run_output with run_id 1", Some(57));
+    assert!(fitted.contains(
+        "elided here, use get_run_output with run_id 1 to read the run's log (57 lines)"
+    ));
+
+    // A line longer than the budget is cut rather than dropped whole
+    let long = format!("=== cargo tree ===\n{}\ndone\n", "é".repeat(1000));
+    let limits = OutputLimits {
+        max_bytes: Some(400),
+        max_lines: None,
+    };
+    let fitted = fit_output(&long, limits, "get_run_output with run_id 1");
+    assert!(fitted.contains(&format!(
+        "{} … ✂️  1900 more bytes on this line",
+        "é".repeat(50)
+    )));
+    assert!(fitted.contains("cut 1 long lines"));
+    assert!(fitted.contains("done\n"));
+}
+
+#[test]
+fn keeps_structured_output_within_limits() {
+    use crate::tools::cargo_utils::{OutputLimits, fit_output, push_structured};
+
+    let records = (0..500)
+        .map(|index| serde_json::json!({ "name": format!("dep{index}"), "version": "1.0.0" }))
+        .collect::<Vec<_>>();
+    let summary = "📦 dep v1.0.0\n".repeat(500);
+    let limits = OutputLimits {
+        max_bytes: Some(3000),
+        max_lines: None,
+    };
+
+    // Over the limit, the summary makes way for the JSON, which keeps a valid prefix
+    let mut result = String::from("=== cargo metadata ===\n");
+    push_structured(
+        &mut result,
+        &summary,
+        "🧾 Structured (JSON):",
+        &records,
+        limits,
+    )
+    .unwrap();
+    let fitted = fit_output(&result, limits, "get_run_output with run_id 1");
+    assert!(!fitted.contains("📦 dep"));
+    let (_, json) = fitted.split_once("🧾 Structured (JSON):\n").unwrap();
+    let (kept, _) = json.split_once("\n… ✂️").unwrap();
+    assert!(
+        serde_json::to_string_pretty(&records)
+            .unwrap()
+            .starts_with(kept)
+    );
+    assert!(kept.contains("\"name\": \"dep5\""));
+
+    // Within it, both are kept
+    let mut result = String::new();
+    push_structured(
+        &mut result,
+        "📦 dep v1.0.0\n",
+        "🧾 Structured (JSON):",
+        &records[..1],
+        limits,
+    )
+    .unwrap();
+    assert!(result.starts_with("📦 dep v1.0.0\n🧾 Structured (JSON):\n[\n  {"));
+}
+
+#[test]
+fn parses_dependency_tree() {
+    use crate::tools::dependency_tree::parse_tree;
+
+    let stdout = r#"0cargo-mcp v0.2.0 (/root/crate)
+1clap v4.5.41
+2clap_derive v4.5.41 (proc-macro)
+1serde feature "derive"
+2serde v1.0.219
+1fieldwork v0.4.6 (proc-macro) (*)
+0other v0.1.0
+"#;
+
+    let roots = parse_tree(stdout);
+    assert_eq!(roots.len(), 2);
+    let root = &roots[0];
+    assert_eq!(root.notes, ["/root/crate"]);
+    assert_eq!(root.children.len(), 3);
+
+    let clap = &root.children[0];
+    assert_eq!(clap.version.as_deref(), Some("4.5.41"));
+    assert_eq!(clap.children[0].notes, ["proc-macro"]);
+
+    let feature = &root.children[1];
+    assert_eq!(feature.feature.as_deref(), Some("derive"));
+    assert_eq!(feature.version, None);
+    assert_eq!(feature.children[0].name, "serde");
+
+    let repeated = &root.children[2];
+    assert!(repeated.repeated);
+    assert_eq!(repeated.notes, ["proc-macro"]);
+}
+
+#[test]
+fn converts_rustdoc_html_to_text() {
+    use crate::tools::rustdoc::html_to_text;
+
+    let html = r##"<p>Parses a <code>&amp;str</code> into a <a href="struct.Value.html"><code>Value</code></a>.</p>
+<h2 id="errors"><a class="doc-anchor" href="#errors">§</a>Errors</h2>
+<ul>
+<li>on invalid JSON</li>
+<li>on &lt;EOF&gt;</li>
+</ul>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let </span>v = from_str(<span class="string">"1"</span>)<span class="question-mark">?</span>;</code></pre></div>"##;
+    assert_eq!(
+        html_to_text(html),
+        "Parses a `&str` into a `Value`.\n\n## Errors\n\n- on invalid JSON\n- on <EOF>\n\n```\nlet v = from_str(\"1\")?;\n```"
+    );
+}
+
+#[test]
+fn reads_rustdoc_search_index() {
+    use crate::tools::search_index::{RUSTDOC_VERSIONS, parse, rustdoc_version};
+    use std::path::Path;
+
+    // The format is rustdoc's own, so the index is only read from the release it was taken from
+    let help = r#"<div id="rustdoc-vars" data-rustdoc-version="1.95.0 (59807616e 2026-04-14)">"#;
+    assert_eq!(rustdoc_version(help), Some((1, 95)));
+    assert!(RUSTDOC_VERSIONS.contains(&(1, 95)));
+    let nightly =
+        r#"<div id="rustdoc-vars" data-rustdoc-version="1.97.0-nightly (e50aa6fba 2026-05-19)">"#;
+    assert!(!RUSTDOC_VERSIONS.contains(&rustdoc_version(nightly).unwrap()));
+
+    // rustdoc 1.95 `cargo doc` output for a crate `tt` with a module `shapes` holding a struct
+    // `Circle` (with a method `area` and an implementation of `Draw`) and an enum `Kind` (with a
+    // variant `Round`), and a trait `Draw` with a method `draw`
+    let root = r#"rr_('{"name":{"N":"Bf","E":"OjAAAAAAAAA=","H":"nfdefhBh"},"path":{"N":"Ac","E":"OzAAAAEAABIABgAJAAYAEQAAABYAAQAZAAAAHAAFACQAAQA=","H":"k+734QTo"},"entry":{"N":"Ag","E":"OjAAAAEAAAAAAA4AEAAAAAAAAgADAAQABgAHABAAEgAUABUAGAAaABsAIgAjAA==","H":"QfGDBoZ2"}}')"#;
+    let read = |column: &str, file: &str, entries: &str| {
+        Ok(match (column, file) {
+            ("name", "9df75e7e1061") => r#"rd_("b()bttcAnycf64cmutdDrawdFromdIntodKinddareaddraw0dfrom0dinto0duniteRoundfBorrowfCirclefResultfTypeIdfborrow0fshapes0gTryFromgTryIntogtype_id0htry_from0htry_into0iBorrowMutireferencejborrow_mut0")"#,
+            ("path", "93eef7e104e8") => r#"rd_("f[1,\"\"]f[3,\"\"]A`[10,\"core::any\"]2f[0,\"\"]n[10,\"tt\",\"tt\"]Ad[10,\"core::convert\"]0Am[6,\"tt::shapes\",\"tt::shapes\"]6Ac[10,\"core::borrow\"]Am[5,\"tt::shapes\",\"tt::shapes\"]Ba[6,\"core::result\",\"core::result\"]Ak[5,\"core::any\",\"core::any\"]h[2,\"tt\"]664;")"#,
+            ("entry", "41f183068676") => entries,
+            _ => anyhow::bail!("unexpected file {column}/{file}.js"),
+        }
+        .to_string())
+    };
+    let entries = r#"rd_("Aa[1,3,0,0,0,0,0,0]Ab[1,10,2,0,0,0,0,0]Ab[1,6,25,0,0,0,0,0]Ae[1,13,25,25,20,0,0,0]Ab[1,12,2,2,6,0,0,0]Ae[1,13,25,25,20,6,0,0]Ae[1,13,25,25,20,7,0,0]Ad[1,13,25,25,9,7,0,0]Ae[1,13,25,25,20,8,0,0]Ad[1,13,25,25,9,8,0,0]Ad[1,15,25,25,9,0,0,0]Ab[1,5,25,0,0,0,0,0]Af[1,13,25,25,20,19,0,0]Ae[1,13,25,25,9,19,0,0]Aa[1,2,2,0,0,0,0,0]Ae[1,13,25,25,20,3,0,0]Ad[1,13,25,25,9,3,0,0]Af[1,13,25,25,20,27,0,0]Ae[1,13,25,25,9,27,0,0]Af[1,13,25,25,20,28,0,0]Ae[1,13,25,25,9,28,0,0]Af[1,13,25,25,20,35,0,0]Ae[1,13,25,25,9,35,0,0]")"#;
+
+    let items = parse(root, |column, file| read(column, file, entries)).unwrap();
+    let listed = items
+        .iter()
+        .map(|item| {
+            (
+                item.path.as_str(),
+                item.kind,
+                item.page.as_path(),
+                item.anchor.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        listed,
+        [
+            ("tt", "mod", Path::new("tt/index.html"), None),
+            ("tt::Draw", "trait", Path::new("tt/trait.Draw.html"), None),
+            (
+                "tt::shapes::Kind",
+                "enum",
+                Path::new("tt/shapes/enum.Kind.html"),
+                None
+            ),
+            (
+                "tt::shapes::Circle::area",
+                "method",
+                Path::new("tt/shapes/struct.Circle.html"),
+                Some("method.area")
+            ),
+            (
+                "tt::Draw::draw",
+                "method",
+                Path::new("tt/trait.Draw.html"),
+                Some("tymethod.draw")
+            ),
+            (
+                "tt::shapes::Kind::Round",
+                "variant",
+                Path::new("tt/shapes/enum.Kind.html"),
+                Some("variant.Round")
+            ),
+            (
+                "tt::shapes::Circle",
+                "struct",
+                Path::new("tt/shapes/struct.Circle.html"),
+                None
+            ),
+            ("tt::shapes", "mod", Path::new("tt/shapes/index.html"), None),
+        ]
+    );
+
+    // An entry that doesn't decode fails the whole index rather than dropping the item
+    let unknown_kind = entries.replacen("Aa[1,3,", "Ab[1,40,", 1);
+    let error = parse(root, |column, file| read(column, file, &unknown_kind)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unrecognised item kind 40 in search index"
+    );
+}
+
+#[test]
+fn renders_rustdoc_json_types() {
+    use crate::tools::rustdoc::render_type;
+    use serde_json::json;
+
+    let path = |name: &str, args: serde_json::Value| json!({"resolved_path": {"path": name, "id": 1, "args": {"angle_bracketed": {"args": args, "constraints": []}}}});
+    let reference = json!({"borrowed_ref": {
+        "lifetime": "'a",
+        "is_mutable": true,
+        "type": path("Vec", json!([{"type": {"generic": "T"}}])),
+    }});
+    assert_eq!(render_type(&reference), "&'a mut Vec<T>");
+
+    let tuple = json!({"tuple": [
+        {"slice": {"primitive": "u8"}},
+        {"raw_pointer": {"is_mutable": false, "type": {"primitive": "str"}}},
+        {"array": {"type": {"primitive": "i32"}, "len": "4"}},
+    ]});
+    assert_eq!(render_type(&tuple), "([u8], *const str, [i32; 4])");
+
+    let boxed = path(
+        "Box",
+        json!([{"type": {"dyn_trait": {"traits": [
+            {"trait": {"path": "Fn", "id": 3, "args": null}, "generic_params": []},
+            {"trait": {"path": "Send", "id": 4, "args": null}, "generic_params": []},
+        ], "lifetime": null}}}]),
+    );
+    assert_eq!(render_type(&boxed), "Box<dyn Fn + Send>");
+
+    let associated = json!({"qualified_path": {
+        "name": "Item",
+        "args": null,
+        "self_type": {"generic": "I"},
+        "trait": {"path": "Iterator", "id": 2, "args": null},
+    }});
+    assert_eq!(render_type(&associated), "<I as Iterator>::Item");
+    assert_eq!(render_type(&json!({"unknown": {}})), "_");
+}
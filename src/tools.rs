@@ -1,16 +1,67 @@
 use crate::state::CargoTools;
-mod cargo_utils;
+pub(crate) mod advisories;
+pub(crate) mod capture;
+pub(crate) mod cargo_utils;
+pub(crate) mod compile_options;
+pub(crate) mod dependency_tree;
+pub(crate) mod diagnostics;
+pub(crate) mod feature_matrix;
+pub(crate) mod file_changes;
+pub(crate) mod lockfile;
+pub(crate) mod metadata;
+pub(crate) mod policy;
+pub(crate) mod registry_index;
+pub(crate) mod rustdoc;
+pub(crate) mod rustfmt_output;
+pub(crate) mod search_index;
+pub(crate) mod test_results;
+pub(crate) mod workspace_deps;
 mcplease::tools!(
     CargoTools,
     (CargoCheck, cargo_check, "cargo_check"),
     (CargoClippy, cargo_clippy, "cargo_clippy"),
     (CargoTest, cargo_test, "cargo_test"),
     (CargoFmtCheck, cargo_fmt_check, "cargo_fmt_check"),
+    (CargoFmt, cargo_fmt, "cargo_fmt"),
+    (CargoFix, cargo_fix, "cargo_fix"),
     (CargoBuild, cargo_build, "cargo_build"),
     (CargoBench, cargo_bench, "cargo_bench"),
     (CargoAdd, cargo_add, "cargo_add"),
     (CargoRemove, cargo_remove, "cargo_remove"),
     (CargoUpdate, cargo_update, "cargo_update"),
+    (CargoOutdated, cargo_outdated, "cargo_outdated"),
+    (
+        CargoWorkspaceDeps,
+        cargo_workspace_deps,
+        "cargo_workspace_deps"
+    ),
     (CargoClean, cargo_clean, "cargo_clean"),
-    (CargoRun, cargo_run, "cargo_run")
+    (CargoRun, cargo_run, "cargo_run"),
+    (
+        CargoFeatureMatrix,
+        cargo_feature_matrix,
+        "cargo_feature_matrix"
+    ),
+    (CargoMetadata, cargo_metadata, "cargo_metadata"),
+    (CargoTree, cargo_tree, "cargo_tree"),
+    (CargoAudit, cargo_audit, "cargo_audit"),
+    (CargoPolicy, cargo_policy, "cargo_policy"),
+    (CargoDoc, cargo_doc, "cargo_doc"),
+    (DocSearch, doc_search, "doc_search"),
+    (JobStatus, job_status, "job_status"),
+    (JobOutput, job_output, "job_output"),
+    (JobWait, job_wait, "job_wait"),
+    (JobCancel, job_cancel, "job_cancel"),
+    (ListRuns, list_runs, "list_runs"),
+    (GetRunOutput, get_run_output, "get_run_output"),
+    (
+        SetWorkingDirectory,
+        set_working_directory,
+        "set_working_directory"
+    ),
+    (
+        GetWorkingDirectory,
+        get_working_directory,
+        "get_working_directory"
+    )
 );
@@ -0,0 +1,238 @@
+use anyhow::{Result, anyhow, bail};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::Path};
+
+/// A RustSec advisory, condensed from its front matter and title
+#[derive(Debug, Clone, Serialize)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: String,
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// CVSS vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvss: Option<String>,
+    /// Set for advisories that are not vulnerabilities: "unmaintained", "unsound" or "notice"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub informational: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Version requirements of releases with the fix
+    pub patched: Vec<String>,
+    /// Version requirements of releases that never had the problem
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unaffected: Vec<String>,
+    #[serde(skip)]
+    pub withdrawn: bool,
+}
+
+#[derive(Deserialize)]
+struct RawAdvisory {
+    advisory: RawMetadata,
+    #[serde(default)]
+    versions: RawVersions,
+}
+
+#[derive(Deserialize)]
+struct RawMetadata {
+    id: String,
+    package: String,
+    #[serde(default)]
+    date: Option<toml::Value>,
+    url: Option<String>,
+    cvss: Option<String>,
+    informational: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Default, Deserialize)]
+struct RawVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+impl Advisory {
+    /// Parse an advisory in the advisory-db's Markdown format: TOML front matter in a
+    /// ```` ```toml ```` block, followed by a heading with the title
+    pub fn parse(contents: &str) -> Result<Self> {
+        let front_matter = contents
+            .trim_start()
+            .strip_prefix("```toml")
+            .and_then(|rest| rest.split_once("\n```"))
+            .ok_or_else(|| anyhow!("missing TOML front matter"))?;
+        let raw: RawAdvisory = toml::from_str(front_matter.0)?;
+        let title = front_matter
+            .1
+            .lines()
+            .find_map(|line| line.strip_prefix("# "))
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        Ok(Self {
+            id: raw.advisory.id,
+            package: raw.advisory.package,
+            title,
+            date: raw
+                .advisory
+                .date
+                .map(|date| date.to_string().trim_matches('"').to_string())
+                .unwrap_or_default(),
+            url: raw.advisory.url,
+            cvss: raw.advisory.cvss,
+            informational: raw.advisory.informational,
+            aliases: raw.advisory.aliases,
+            patched: raw.versions.patched,
+            unaffected: raw.versions.unaffected,
+            withdrawn: raw.advisory.withdrawn.is_some(),
+        })
+    }
+
+    /// Whether `version` is neither patched nor unaffected
+    pub fn affects(&self, version: &Version) -> bool {
+        let matches = |requirements: &[String]| {
+            requirements.iter().any(|requirement| {
+                VersionReq::parse(requirement).is_ok_and(|requirement| requirement.matches(version))
+            })
+        };
+        !matches(&self.patched) && !matches(&self.unaffected)
+    }
+
+    /// The CVSS base score and its severity rating, for CVSS 3 vectors
+    pub fn severity(&self) -> Option<(f64, &'static str)> {
+        let score = cvss3_base_score(self.cvss.as_deref()?)?;
+        let rating = match score {
+            0.0 => "none",
+            s if s < 4.0 => "low",
+            s if s < 7.0 => "medium",
+            s if s < 9.0 => "high",
+            _ => "critical",
+        };
+        Some((score, rating))
+    }
+}
+
+/// Load the advisories for `packages` from a clone of the RustSec advisory database, which
+/// keeps them in `crates/<name>/RUSTSEC-*.md`. Withdrawn advisories are left out.
+pub fn load(db_path: &Path, packages: &HashSet<&str>) -> Result<Vec<Advisory>> {
+    let crates_dir = db_path.join("crates");
+    if !crates_dir.is_dir() {
+        bail!(
+            "No advisory database at {}. Clone it with `git clone \
+             https://github.com/rustsec/advisory-db {}` and pull it to update.",
+            db_path.display(),
+            db_path.display()
+        );
+    }
+
+    let mut advisories = Vec::new();
+    for package in packages {
+        let Ok(entries) = fs::read_dir(crates_dir.join(package)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "md") {
+                continue;
+            }
+            let contents = fs::read_to_string(&path)?;
+            match Advisory::parse(&contents) {
+                Ok(advisory) if !advisory.withdrawn => advisories.push(advisory),
+                Ok(_) => {}
+                Err(e) => log::warn!("skipping advisory {}: {e}", path.display()),
+            }
+        }
+    }
+    advisories.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(advisories)
+}
+
+/// The CVSS 3.0/3.1 base score of a vector, following the specification's formula
+fn cvss3_base_score(vector: &str) -> Option<f64> {
+    if !vector.starts_with("CVSS:3") {
+        return None;
+    }
+    let metric = |name: &str| {
+        vector
+            .split('/')
+            .find_map(|part| part.strip_prefix(name)?.strip_prefix(':'))
+    };
+
+    let changed = match metric("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges_required = match (metric("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_metric = |name: &str| match metric(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let (confidentiality, integrity, availability) = (
+        impact_metric("C")?,
+        impact_metric("I")?,
+        impact_metric("A")?,
+    );
+
+    let impact_subscore = 1.0 - (1.0 - confidentiality) * (1.0 - integrity) * (1.0 - availability);
+    let impact = if changed {
+        7.52 * (impact_subscore - 0.029) - 3.25 * (impact_subscore - 0.02f64).powi(15)
+    } else {
+        6.42 * impact_subscore
+    };
+    let exploitability =
+        8.22 * attack_vector * attack_complexity * privileges_required * user_interaction;
+
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let score = if changed {
+        (1.08 * (impact + exploitability)).min(10.0)
+    } else {
+        (impact + exploitability).min(10.0)
+    };
+    Some(round_up(score))
+}
+
+/// Round up to one decimal place, avoiding floating point artifacts like 4.000001 → 4.1
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as u64;
+    if scaled.is_multiple_of(10_000) {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
@@ -0,0 +1,307 @@
+use crate::progress::ProgressTracker;
+use crate::tools::cargo_utils::CargoOutput;
+use crate::tools::diagnostics::human_readable_line;
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    process::{Child, ExitStatus},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Upper bound on the output kept for a single command. Beyond this, the oldest lines after
+/// the first half are dropped so both the beginning and the end of the output survive.
+const MAX_CAPTURED_BYTES: usize = 32 * 1024 * 1024;
+
+/// Which pipe a line of output came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A line of output and when it arrived, relative to when the command started
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub stream: Stream,
+    pub elapsed: Duration,
+    pub text: String,
+}
+
+impl OutputLine {
+    /// The line prefixed with its arrival time and stream, e.g. `[  1.25s err] Compiling foo`
+    pub fn timestamped(&self) -> String {
+        let stream = match self.stream {
+            Stream::Stdout => "out",
+            Stream::Stderr => "err",
+        };
+        format!(
+            "[{:>7.2}s {stream}] {}",
+            self.elapsed.as_secs_f64(),
+            self.text
+        )
+    }
+}
+
+/// The text of a line without the `[   1.25s err] ` prefix added by
+/// [`OutputLine::timestamped`], if it has one
+pub fn strip_timestamp(line: &str) -> &str {
+    let Some((prefix, text)) = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    else {
+        return line;
+    };
+    let is_timestamp = prefix
+        .strip_suffix(" out")
+        .or_else(|| prefix.strip_suffix(" err"))
+        .and_then(|elapsed| elapsed.trim_start().strip_suffix('s'))
+        .is_some_and(|seconds| seconds.parse::<f64>().is_ok());
+    if is_timestamp { text } else { line }
+}
+
+/// Lines as a terminal would have shown them, optionally prefixed with their arrival time.
+/// Compiler messages on stdout are rendered from cargo's JSON.
+pub fn display_lines<'a>(
+    lines: impl IntoIterator<Item = &'a OutputLine>,
+    timestamps: bool,
+) -> Vec<String> {
+    let mut display = Vec::new();
+    for line in lines {
+        // Only the first line of a multi-line compiler message gets a timestamp
+        let text = match line.stream {
+            Stream::Stdout => human_readable_line(&line.text),
+            Stream::Stderr => Some(line.text.clone()),
+        };
+        let Some(text) = text else {
+            continue;
+        };
+
+        let text = if timestamps {
+            OutputLine {
+                text,
+                ..line.clone()
+            }
+            .timestamped()
+        } else {
+            text
+        };
+        display.extend(text.split('\n').map(String::from));
+    }
+    display
+}
+
+/// Lines from stdout and stderr interleaved in arrival order, bounded to
+/// [`MAX_CAPTURED_BYTES`] by keeping the first and most recent lines
+#[derive(Debug, Default)]
+pub struct OutputLog {
+    head: Vec<OutputLine>,
+    head_bytes: usize,
+    tail: VecDeque<OutputLine>,
+    tail_bytes: usize,
+    omitted: usize,
+}
+
+impl OutputLog {
+    fn push(&mut self, line: OutputLine) {
+        let bytes = line.text.len() + 1;
+        if self.omitted == 0 && self.head_bytes + bytes <= MAX_CAPTURED_BYTES / 2 {
+            self.head_bytes += bytes;
+            self.head.push(line);
+            return;
+        }
+
+        self.tail_bytes += bytes;
+        self.tail.push_back(line);
+        while self.tail_bytes > MAX_CAPTURED_BYTES / 2 {
+            let Some(dropped) = self.tail.pop_front() else {
+                break;
+            };
+            self.tail_bytes -= dropped.text.len() + 1;
+            self.omitted += 1;
+        }
+    }
+
+    /// Total number of lines received, including any that were dropped
+    pub fn len(&self) -> usize {
+        self.head.len() + self.omitted + self.tail.len()
+    }
+
+    /// Lines with their absolute line numbers in `[start, end)`, skipping any that were dropped
+    pub fn range(&self, start: usize, end: usize) -> Vec<(usize, &OutputLine)> {
+        let tail_start = self.head.len() + self.omitted;
+        self.head
+            .iter()
+            .enumerate()
+            .chain(
+                self.tail
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| (tail_start + index, line)),
+            )
+            .filter(|(index, _)| (start..end).contains(index))
+            .collect()
+    }
+
+    /// All retained lines in arrival order
+    pub fn lines(&self) -> impl Iterator<Item = &OutputLine> {
+        self.head.iter().chain(&self.tail)
+    }
+
+    /// The text written to one stream, with a marker where lines were dropped
+    pub fn text(&self, stream: Stream) -> String {
+        let mut text = String::new();
+        for line in self.head.iter().filter(|line| line.stream == stream) {
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+
+        if self.omitted > 0 {
+            text.push_str(&format!(
+                "… {} lines omitted (output exceeded {} MiB) …\n",
+                self.omitted,
+                MAX_CAPTURED_BYTES / 1024 / 1024
+            ));
+        }
+
+        for line in self.tail.iter().filter(|line| line.stream == stream) {
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Reader threads draining a child's stdout and stderr into a shared [`OutputLog`] while it runs,
+/// so that a chatty process can never fill a pipe buffer and block
+#[derive(Debug)]
+pub struct Capture {
+    started_at: Instant,
+    log: Arc<Mutex<OutputLog>>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl Capture {
+    /// Start draining the child's piped stdout and stderr, feeding each line to `progress`
+    pub fn start(child: &mut Child, progress: Option<ProgressTracker>) -> Self {
+        let started_at = Instant::now();
+        let log = Arc::new(Mutex::new(OutputLog::default()));
+        let progress = progress.map(|progress| Arc::new(Mutex::new(progress)));
+
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            let reader = Reader::new(Stream::Stdout, &log, &progress, started_at);
+            readers.push(reader.spawn(stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let reader = Reader::new(Stream::Stderr, &log, &progress, started_at);
+            readers.push(reader.spawn(stderr));
+        }
+
+        Self {
+            started_at,
+            log,
+            readers,
+        }
+    }
+
+    /// Shared handle to the output collected so far
+    pub fn log(&self) -> Arc<Mutex<OutputLog>> {
+        Arc::clone(&self.log)
+    }
+
+    /// Wait for the pipes to close and assemble the command's output. The child must already
+    /// have exited (or been killed along with its process group), or this blocks until it does.
+    pub fn finish(
+        self,
+        command: String,
+        status: ExitStatus,
+        timed_out: Option<u64>,
+    ) -> CargoOutput {
+        for reader in self.readers {
+            let _ = reader.join();
+        }
+
+        let duration = self.started_at.elapsed();
+        let log = self.log.lock().unwrap();
+        CargoOutput {
+            command,
+            status,
+            duration,
+            stdout: log.text(Stream::Stdout),
+            stderr: log.text(Stream::Stderr),
+            lines: log.lines().cloned().collect(),
+            timed_out,
+            cancelled: false,
+        }
+    }
+}
+
+/// One pipe's reader thread
+struct Reader {
+    stream: Stream,
+    log: Arc<Mutex<OutputLog>>,
+    progress: Option<Arc<Mutex<ProgressTracker>>>,
+    started_at: Instant,
+}
+
+impl Reader {
+    fn new(
+        stream: Stream,
+        log: &Arc<Mutex<OutputLog>>,
+        progress: &Option<Arc<Mutex<ProgressTracker>>>,
+        started_at: Instant,
+    ) -> Self {
+        Self {
+            stream,
+            log: Arc::clone(log),
+            progress: progress.clone(),
+            started_at,
+        }
+    }
+
+    fn spawn(self, pipe: impl Read + Send + 'static) -> JoinHandle<()> {
+        thread::spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+
+                let text = String::from_utf8_lossy(&buf);
+                let text = text.trim_end_matches(['\n', '\r']);
+                if text.contains('\r') {
+                    // Carriage returns redraw a status line in place, such as cargo's progress
+                    // bar, so each segment is its own line and the bar itself is not kept
+                    for segment in text.split('\r') {
+                        if !segment.trim().is_empty() {
+                            self.push(segment, segment.trim().starts_with("Building ["));
+                        }
+                    }
+                } else {
+                    self.push(text, false);
+                }
+            }
+        })
+    }
+
+    /// Record a line, unless it is a transient status line, and report it to the progress
+    /// tracker
+    fn push(&self, text: &str, transient: bool) {
+        if let Some(progress) = &self.progress {
+            progress.lock().unwrap().observe(text);
+        }
+
+        if !transient {
+            self.log.lock().unwrap().push(OutputLine {
+                stream: self.stream,
+                elapsed: self.started_at.elapsed(),
+                text: text.to_string(),
+            });
+        }
+    }
+}
@@ -1,17 +1,26 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
-use anyhow::{Result, anyhow};
+use crate::tools::cargo_utils::{
+    RunOptions, create_cargo_command, execute_or_spawn, execute_with_lockfile_diff,
+};
+use anyhow::{Result, anyhow, bail};
 use mcplease::{
     traits::{Tool, WithExamples},
     types::Example,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Add dependencies to Cargo.toml using cargo add
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_add")]
 pub struct CargoAdd {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
     /// List of dependencies to add (e.g., ['serde', 'tokio@1.0'])
     pub dependencies: Vec<String>,
 
@@ -25,16 +34,100 @@ pub struct CargoAdd {
     #[arg(long)]
     pub dev: Option<bool>,
 
+    /// Add as build dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub build: Option<bool>,
+
+    /// Add as dependencies for a target platform only, e.g. 'cfg(unix)' or
+    /// 'x86_64-pc-windows-msvc'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub target: Option<String>,
+
     /// Add as optional dependencies
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
     pub optional: Option<bool>,
 
-    /// Optional features to enable
+    /// Optional features to enable. When adding several dependencies, qualify each feature
+    /// with its dependency (e.g., 'serde/derive') or use dependency_features.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
     pub features: Option<Vec<String>>,
 
+    /// Features to enable per dependency, e.g. {"serde": ["derive"], "tokio": ["full"]}
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub dependency_features: Option<BTreeMap<String, Vec<String>>>,
+
+    /// Disable the dependencies' default features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_default_features: Option<bool>,
+
+    /// Re-enable the default features of dependencies that had them disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub default_features: Option<bool>,
+
+    /// Name to use for the dependency in code, for a single dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub rename: Option<String>,
+
+    /// Git repository URL to add the dependency from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub git: Option<String>,
+
+    /// Git branch to use with git
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    /// Git tag to use with git
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Git commit to use with git
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub rev: Option<String>,
+
+    /// Local directory to add the dependency from (supports `~`), relative to the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub dependency_path: Option<String>,
+
+    /// Registry to add the dependency from, as named in .cargo/config.toml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub registry: Option<String>,
+
+    /// Show what would be added without changing Cargo.toml or Cargo.lock
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub dry_run: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -53,48 +146,63 @@ impl WithExamples for CargoAdd {
                 description: "Add a simple dependency",
                 item: Self {
                     dependencies: vec!["serde".into()],
-                    package: None,
-                    dev: None,
-                    optional: None,
-                    features: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Add multiple dependencies with versions",
                 item: Self {
                     dependencies: vec!["serde@1.0".into(), "tokio@1.0".into()],
-                    package: None,
-                    dev: None,
-                    optional: None,
-                    features: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Add a dev dependency",
                 item: Self {
                     dependencies: vec!["criterion".into()],
-                    package: None,
                     dev: Some(true),
-                    optional: None,
-                    features: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Add dependency with features",
                 item: Self {
                     dependencies: vec!["tokio".into()],
-                    package: None,
-                    dev: None,
-                    optional: None,
                     features: Some(vec!["full".into()]),
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Add several dependencies, each with its own features",
+                item: Self {
+                    dependencies: vec!["serde".into(), "tokio".into()],
+                    dependency_features: Some(BTreeMap::from([
+                        ("serde".into(), vec!["derive".into()]),
+                        ("tokio".into(), vec!["macros".into(), "rt".into()]),
+                    ])),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Add a dependency from a git branch under another name",
+                item: Self {
+                    dependencies: vec!["regex".into()],
+                    git: Some("https://github.com/rust-lang/regex".into()),
+                    branch: Some("master".into()),
+                    rename: Some("regex_next".into()),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Preview adding a Windows-only build dependency without default \
+                              features",
+                item: Self {
+                    dependencies: vec!["winres".into()],
+                    build: Some(true),
+                    target: Some("cfg(windows)".into()),
+                    no_default_features: Some(true),
+                    dry_run: Some(true),
+                    ..Self::default()
                 },
             },
         ]
@@ -103,17 +211,29 @@ impl WithExamples for CargoAdd {
 
 impl Tool<CargoTools> for CargoAdd {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_add", &self);
         if self.dependencies.is_empty() {
             return Err(anyhow!("No dependencies specified"));
         }
+        if self.git.is_none() && (self.branch.is_some() || self.tag.is_some() || self.rev.is_some())
+        {
+            bail!("branch, tag and rev need a git repository");
+        }
 
-        let project_path = state.ensure_rust_project()?;
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
         let toolchain = self
             .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let mut args = vec!["add"];
 
@@ -125,17 +245,77 @@ impl Tool<CargoTools> for CargoAdd {
             args.push("--dev");
         }
 
+        if self.build.unwrap_or(false) {
+            args.push("--build");
+        }
+
+        if let Some(ref target) = self.target {
+            args.extend_from_slice(&["--target", target]);
+        }
+
         if self.optional.unwrap_or(false) {
             args.push("--optional");
         }
 
+        // Features for particular dependencies are passed as "dependency/feature"
+        let mut features = self.features.clone().unwrap_or_default();
+        for (dependency, dependency_features) in self.dependency_features.iter().flatten() {
+            features.extend(
+                dependency_features
+                    .iter()
+                    .map(|feature| format!("{dependency}/{feature}")),
+            );
+        }
         let features_str;
+        if !features.is_empty() {
+            features_str = features.join(",");
+            args.extend_from_slice(&["--features", &features_str]);
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            args.push("--no-default-features");
+        }
+
+        if self.default_features.unwrap_or(false) {
+            args.push("--default-features");
+        }
+
+        if let Some(ref rename) = self.rename {
+            args.extend_from_slice(&["--rename", rename]);
+        }
 
-        if let Some(ref features) = self.features
-            && !features.is_empty() {
-                features_str = features.join(",");
-                args.extend_from_slice(&["--features", &features_str]);
-            }
+        if let Some(ref git) = self.git {
+            args.extend_from_slice(&["--git", git]);
+        }
+
+        if let Some(ref branch) = self.branch {
+            args.extend_from_slice(&["--branch", branch]);
+        }
+
+        if let Some(ref tag) = self.tag {
+            args.extend_from_slice(&["--tag", tag]);
+        }
+
+        if let Some(ref rev) = self.rev {
+            args.extend_from_slice(&["--rev", rev]);
+        }
+
+        let dependency_path = self
+            .dependency_path
+            .as_deref()
+            .map(|path| shellexpand::tilde(path).into_owned());
+        if let Some(ref dependency_path) = dependency_path {
+            args.extend_from_slice(&["--path", dependency_path]);
+        }
+
+        if let Some(ref registry) = self.registry {
+            args.extend_from_slice(&["--registry", registry]);
+        }
+
+        let dry_run = self.dry_run.unwrap_or(false);
+        if dry_run {
+            args.push("--dry-run");
+        }
 
         // Add the dependencies
         for dep in &self.dependencies {
@@ -143,6 +323,18 @@ impl Tool<CargoTools> for CargoAdd {
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo add", None)
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        if dry_run {
+            // Nothing is written, so there's no lockfile change to report
+            execute_or_spawn(state, cmd, &project_path, "cargo add", options)
+        } else {
+            execute_with_lockfile_diff(state, cmd, &project_path, "cargo add", options)
+        }
     }
 }
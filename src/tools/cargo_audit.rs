@@ -0,0 +1,298 @@
+use crate::project::find_workspace_root;
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::advisories::{self, Advisory};
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, fit_output, fit_run_output, run_in_foreground,
+};
+use crate::tools::lockfile::Lockfile;
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::PathBuf,
+};
+
+/// Check the dependencies in Cargo.lock for known vulnerabilities, using a local clone of the
+/// RustSec advisory database and no network access
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_audit")]
+pub struct CargoAudit {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Directory of a clone of https://github.com/rustsec/advisory-db (defaults to
+    /// CARGO_MCP_ADVISORY_DB, or ~/.cargo/advisory-db where cargo-audit keeps it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub db_path: Option<String>,
+
+    /// Advisory IDs to leave out of the report, e.g. "RUSTSEC-2023-0071"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub ignore: Option<Vec<String>>,
+
+    /// Also report unmaintained, unsound and other informational advisories (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub informational: Option<bool>,
+
+    /// Optional timeout in seconds for generating a missing Cargo.lock, after which cargo and
+    /// everything it started is killed (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+/// An advisory that applies to a locked package
+#[derive(Debug, Serialize)]
+struct Finding {
+    #[serde(flatten)]
+    advisory: Advisory,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    /// From a path package (usually a workspace member) to the affected package
+    dependency_path: Vec<String>,
+}
+
+impl WithExamples for CargoAudit {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Audit the project's Cargo.lock",
+                item: Self::default(),
+            },
+            Example {
+                description: "Audit against an advisory database in a custom location, ignoring \
+                              an accepted advisory",
+                item: Self {
+                    db_path: Some("~/src/advisory-db".into()),
+                    ignore: Some(vec!["RUSTSEC-2023-0071".into()]),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Only report vulnerabilities, not unmaintained crates",
+                item: Self {
+                    informational: Some(false),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoAudit {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_audit", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let db_path = match self
+            .db_path
+            .clone()
+            .or_else(|| std::env::var("CARGO_MCP_ADVISORY_DB").ok())
+        {
+            Some(path) => PathBuf::from(&*shellexpand::tilde(&path)),
+            None => std::env::var_os("CARGO_HOME")
+                .map(PathBuf::from)
+                .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+                .unwrap_or_default()
+                .join("advisory-db"),
+        };
+
+        // Like cargo audit, resolve the dependencies first if there is no lockfile yet
+        let workspace_root = find_workspace_root(&project_path);
+        if !workspace_root.join("Cargo.lock").is_file() {
+            let cmd = create_cargo_command(
+                &["generate-lockfile"],
+                toolchain.as_deref(),
+                self.cargo_env.as_ref(),
+            );
+            let options = RunOptions {
+                timeout_secs,
+                limits,
+                invocation,
+                toolchain,
+                ..RunOptions::default()
+            };
+            let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+            if !output.succeeded() {
+                let result =
+                    OutputFormat::Plain.render(&output, "cargo generate-lockfile", &project_path);
+                bail!(fit_run_output(&result, limits, &full_output, &output));
+            }
+        }
+        let lockfile = Lockfile::load(&workspace_root)?;
+        let names = lockfile
+            .packages
+            .iter()
+            .filter(|package| package.is_from_crates_io())
+            .map(|package| package.name.as_str())
+            .collect::<HashSet<_>>();
+        let advisories = advisories::load(&db_path, &names)?;
+
+        let ignore = self.ignore.unwrap_or_default();
+        let informational = self.informational.unwrap_or(true);
+        let mut findings = Vec::new();
+        for (index, package) in lockfile.packages.iter().enumerate() {
+            if !package.is_from_crates_io() {
+                continue;
+            }
+            let Ok(version) = Version::parse(&package.version) else {
+                continue;
+            };
+            for advisory in &advisories {
+                if advisory.package != package.name
+                    || ignore.contains(&advisory.id)
+                    || advisory.informational.is_some() && !informational
+                    || !advisory.affects(&version)
+                {
+                    continue;
+                }
+                let severity = advisory.severity();
+                findings.push(Finding {
+                    advisory: advisory.clone(),
+                    version: package.version.clone(),
+                    severity: severity.map(|(_, rating)| rating),
+                    score: severity.map(|(score, _)| score),
+                    dependency_path: lockfile
+                        .dependency_path(index)
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                });
+            }
+        }
+
+        let (vulnerabilities, warnings): (Vec<_>, Vec<_>) = findings
+            .iter()
+            .partition(|finding| finding.advisory.informational.is_none());
+
+        let mut result = format!(
+            "=== cargo audit ===\n📁 Working directory: {}\n🔒 Lockfile: {} ({} packages)\n\
+             🗃️  Advisory database: {}\n\n",
+            project_path.display(),
+            workspace_root.join("Cargo.lock").display(),
+            lockfile.packages.len(),
+            db_path.display()
+        );
+        if vulnerabilities.is_empty() {
+            result.push_str("✅ No known vulnerabilities\n");
+        } else {
+            let _ = writeln!(
+                result,
+                "❌ {} vulnerabilit{} found",
+                vulnerabilities.len(),
+                if vulnerabilities.len() == 1 {
+                    "y"
+                } else {
+                    "ies"
+                }
+            );
+        }
+        if !warnings.is_empty() {
+            let _ = writeln!(
+                result,
+                "⚠️  {} informational advisor{} (unmaintained, unsound or notices)",
+                warnings.len(),
+                if warnings.len() == 1 { "y" } else { "ies" }
+            );
+        }
+
+        for finding in vulnerabilities.iter().chain(&warnings) {
+            let advisory = &finding.advisory;
+            let kind = match (&advisory.informational, finding.severity, finding.score) {
+                (Some(kind), _, _) => kind.clone(),
+                (None, Some(severity), Some(score)) => format!("{severity}, CVSS {score:.1}"),
+                (None, _, _) => "severity unknown".into(),
+            };
+            let icon = if advisory.informational.is_some() {
+                "⚠️ "
+            } else {
+                "🚨"
+            };
+            let _ = writeln!(
+                result,
+                "\n{icon} {} ({kind}): {}",
+                advisory.id, advisory.title
+            );
+            let _ = writeln!(result, "   📦 {} {}", advisory.package, finding.version);
+            if advisory.patched.is_empty() {
+                result.push_str("   🩹 Patched: no fixed release\n");
+            } else {
+                let _ = writeln!(result, "   🩹 Patched: {}", advisory.patched.join(" or "));
+            }
+            let _ = writeln!(
+                result,
+                "   🔗 Dependency path: {}",
+                finding.dependency_path.join(" → ")
+            );
+            if !advisory.aliases.is_empty() {
+                let _ = writeln!(result, "   🏷️  Aliases: {}", advisory.aliases.join(", "));
+            }
+            let _ = writeln!(
+                result,
+                "   🌐 https://rustsec.org/advisories/{}.html",
+                advisory.id
+            );
+            if let Some(url) = &advisory.url {
+                let _ = writeln!(result, "   📎 {url}");
+            }
+        }
+
+        if !findings.is_empty() {
+            result.push_str("\n🧾 Structured findings (JSON):\n");
+            result.push_str(&serde_json::to_string(&findings).unwrap_or_default());
+            result.push('\n');
+        }
+
+        Ok(fit_output(&result, limits, "a larger max_output_bytes"))
+    }
+}
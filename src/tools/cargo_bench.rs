@@ -1,5 +1,7 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{OutputFormat, RunOptions, create_cargo_command, execute_or_spawn};
+use crate::tools::compile_options::{CompileOptions, TargetSelection};
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -9,13 +11,22 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Run cargo bench to execute benchmarks
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_bench")]
 pub struct CargoBench {
-    /// Optional package name to benchmark (for workspaces)
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub package: Option<String>,
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub targets: TargetSelection,
 
     /// Optional specific benchmark name to run
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -27,6 +38,29 @@ pub struct CargoBench {
     #[arg(long)]
     pub baseline: Option<String>,
 
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub background: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -43,42 +77,30 @@ impl WithExamples for CargoBench {
         vec![
             Example {
                 description: "Run all benchmarks",
-                item: Self {
-                    package: None,
-                    bench_name: None,
-                    baseline: None,
-                    toolchain: None,
-                    cargo_env: None,
-                },
+                item: Self::default(),
             },
             Example {
                 description: "Run a specific benchmark",
                 item: Self {
-                    package: None,
                     bench_name: Some("my_benchmark".into()),
-                    baseline: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Run benchmarks for a specific package",
                 item: Self {
-                    package: Some("my-lib".into()),
-                    bench_name: None,
-                    baseline: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        ..CompileOptions::default()
+                    },
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Run benchmarks with a baseline for comparison",
                 item: Self {
-                    package: None,
-                    bench_name: None,
                     baseline: Some("main".into()),
-                    toolchain: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
         ]
@@ -87,18 +109,28 @@ impl WithExamples for CargoBench {
 
 impl Tool<CargoTools> for CargoBench {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_bench", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let mut args = vec!["bench"];
-        
-        if let Some(ref package) = self.package {
-            args.extend_from_slice(&["--package", package]);
-        }
+
+        let compile_args = self.compile.args();
+        let target_args = self.targets.args();
+        args.extend(compile_args.iter().map(String::as_str));
+        args.extend(target_args.iter().map(String::as_str));
 
         if let Some(ref bench_name) = self.bench_name {
             args.push(bench_name);
@@ -109,6 +141,19 @@ impl Tool<CargoTools> for CargoBench {
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo bench", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo bench",
+            RunOptions {
+                background: self.background.unwrap_or(false),
+                timeout_secs,
+                format: OutputFormat::Plain,
+                limits,
+                invocation,
+                toolchain,
+            },
+        )
     }
 }
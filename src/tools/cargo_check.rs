@@ -1,5 +1,8 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{OutputFormat, RunOptions, create_cargo_command, execute_or_spawn};
+use crate::tools::compile_options::{CompileOptions, TargetSelection};
+use crate::tools::diagnostics::MESSAGE_FORMAT_JSON;
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -9,13 +12,45 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Run cargo check to verify the code compiles
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_check")]
 pub struct CargoCheck {
-    /// Optional package name to check (for workspaces)
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub package: Option<String>,
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub targets: TargetSelection,
+
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub background: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
 
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -33,33 +68,35 @@ impl WithExamples for CargoCheck {
         vec![
             Example {
                 description: "Basic cargo check in current project",
+                item: Self::default(),
+            },
+            Example {
+                description: "Check a specific package in a workspace",
                 item: Self {
-                    package: None,
-                    toolchain: None,
-                    cargo_env: None,
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        ..CompileOptions::default()
+                    },
+                    ..Self::default()
                 },
             },
             Example {
-                description: "Check a specific package in a workspace",
+                description: "Check a project other than the selected one",
                 item: Self {
-                    package: Some("my-lib".into()),
-                    toolchain: None,
-                    cargo_env: None,
+                    path: Some("~/code/other-project".into()),
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Check using nightly toolchain",
                 item: Self {
-                    package: None,
                     toolchain: Some("nightly".into()),
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Check with custom environment variables",
                 item: Self {
-                    package: None,
-                    toolchain: None,
                     cargo_env: Some(
                         [
                             ("RUSTFLAGS".into(), "-D warnings".into()),
@@ -67,6 +104,7 @@ impl WithExamples for CargoCheck {
                         ]
                         .into(),
                     ),
+                    ..Self::default()
                 },
             },
         ]
@@ -75,20 +113,43 @@ impl WithExamples for CargoCheck {
 
 impl Tool<CargoTools> for CargoCheck {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_check", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
         let toolchain = self
             .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
-        let mut args = vec!["check"];
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
-        if let Some(ref package) = self.package {
-            args.extend_from_slice(&["--package", package]);
-        }
+        let mut args = vec!["check", MESSAGE_FORMAT_JSON];
+
+        let compile_args = self.compile.args();
+        let target_args = self.targets.args();
+        args.extend(compile_args.iter().map(String::as_str));
+        args.extend(target_args.iter().map(String::as_str));
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo check", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo check",
+            RunOptions {
+                background: self.background.unwrap_or(false),
+                timeout_secs,
+                format: OutputFormat::Diagnostics,
+                limits,
+                invocation,
+                toolchain,
+            },
+        )
     }
 }
@@ -1,5 +1,6 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{RunOptions, create_cargo_command, execute_or_spawn};
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -12,11 +13,34 @@ use std::collections::HashMap;
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_clean")]
 pub struct CargoClean {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
     /// Optional package to clean artifacts for
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
     pub package: Option<String>,
 
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -34,7 +58,11 @@ impl WithExamples for CargoClean {
             Example {
                 description: "Clean all build artifacts",
                 item: Self {
+                    path: None,
                     package: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -42,7 +70,11 @@ impl WithExamples for CargoClean {
             Example {
                 description: "Clean artifacts for a specific package",
                 item: Self {
+                    path: None,
                     package: Some("my-lib".into()),
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -53,20 +85,41 @@ impl WithExamples for CargoClean {
 
 impl Tool<CargoTools> for CargoClean {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_clean", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let mut args = vec!["clean"];
-        
+
         if let Some(ref package) = self.package {
             args.extend_from_slice(&["--package", package]);
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo clean", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo clean",
+            RunOptions {
+                timeout_secs,
+                limits,
+                invocation,
+                toolchain,
+                ..RunOptions::default()
+            },
+        )
     }
 }
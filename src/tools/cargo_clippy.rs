@@ -1,6 +1,9 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
-use anyhow::Result;
+use crate::tools::cargo_utils::{OutputFormat, RunOptions, create_cargo_command, execute_or_spawn};
+use crate::tools::compile_options::{CompileOptions, TargetSelection};
+use crate::tools::diagnostics::MESSAGE_FORMAT_JSON;
+use anyhow::{Result, bail};
 use mcplease::{
     traits::{Tool, WithExamples},
     types::Example,
@@ -9,13 +12,73 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Run cargo clippy for linting suggestions
-#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_clippy")]
 pub struct CargoClippy {
-    /// Optional package name to lint (for workspaces)
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub package: Option<String>,
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub targets: TargetSelection,
+
+    /// Lints or lint groups to warn about, e.g. "clippy::pedantic"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub warn: Option<Vec<String>>,
+
+    /// Lints or lint groups to report as errors, e.g. "warnings" to fail on any warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub deny: Option<Vec<String>>,
+
+    /// Lints or lint groups to silence. These are passed after `warn` and `deny`, so they can
+    /// carve single lints out of a group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub allow: Option<Vec<String>>,
+
+    /// Lints or lint groups to report as errors that can't be allowed in the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub forbid: Option<Vec<String>>,
+
+    /// Only use the lint levels from the project's `[lints]` table and the code, passing no
+    /// lint levels to clippy. By default warnings are denied unless warn, deny, allow or
+    /// forbid is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub project_lints_only: Option<bool>,
+
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub background: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
 
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,39 +100,50 @@ impl WithExamples for CargoClippy {
     fn examples() -> Vec<Example<Self>> {
         vec![
             Example {
-                description: "Basic cargo clippy in current project",
+                description: "Basic cargo clippy in current project, denying warnings",
+                item: Self::default(),
+            },
+            Example {
+                description: "Run clippy on a specific package",
                 item: Self {
-                    package: None,
-                    toolchain: None,
-                    fix: None,
-                    cargo_env: None,
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        ..CompileOptions::default()
+                    },
+                    ..Self::default()
                 },
             },
             Example {
-                description: "Run clippy on a specific package",
+                description: "Warn about pedantic lints in every target except one noisy lint",
+                item: Self {
+                    targets: TargetSelection {
+                        all_targets: Some(true),
+                        ..TargetSelection::default()
+                    },
+                    warn: Some(vec!["clippy::pedantic".into()]),
+                    allow: Some(vec!["clippy::module_name_repetitions".into()]),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Lint with only the levels configured in Cargo.toml",
                 item: Self {
-                    package: Some("my-lib".into()),
-                    toolchain: None,
-                    fix: None,
-                    cargo_env: None,
+                    project_lints_only: Some(true),
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Run clippy with automatic fixes",
                 item: Self {
-                    package: None,
-                    toolchain: None,
                     fix: Some(true),
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
             Example {
                 description: "Run clippy with nightly toolchain",
                 item: Self {
-                    package: None,
                     toolchain: Some("nightly".into()),
-                    fix: None,
-                    cargo_env: None,
+                    ..Self::default()
                 },
             },
         ]
@@ -78,27 +152,73 @@ impl WithExamples for CargoClippy {
 
 impl Tool<CargoTools> for CargoClippy {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_clippy", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
 
-        let mut args = vec!["clippy"];
-        
-        if let Some(ref package) = self.package {
-            args.extend_from_slice(&["--package", package]);
-        }
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let mut args = vec!["clippy", MESSAGE_FORMAT_JSON];
+
+        let compile_args = self.compile.args();
+        let target_args = self.targets.args();
+        args.extend(compile_args.iter().map(String::as_str));
+        args.extend(target_args.iter().map(String::as_str));
 
         if self.fix.unwrap_or(false) {
             args.push("--fix");
         }
 
-        // Add clippy arguments
-        args.extend_from_slice(&["--", "-D", "warnings"]);
+        // Add lint levels for clippy. Later flags take precedence, so single lints can be
+        // allowed out of a group that is warned about or denied.
+        let levels = [
+            ("-W", &self.warn),
+            ("-D", &self.deny),
+            ("-A", &self.allow),
+            ("-F", &self.forbid),
+        ];
+        let has_levels = levels
+            .iter()
+            .any(|(_, lints)| lints.as_ref().is_some_and(|lints| !lints.is_empty()));
+        if self.project_lints_only.unwrap_or(false) {
+            if has_levels {
+                bail!("project_lints_only can't be combined with warn, deny, allow or forbid");
+            }
+        } else if has_levels {
+            args.push("--");
+            for (flag, lints) in levels {
+                for lint in lints.iter().flatten() {
+                    args.extend_from_slice(&[flag, lint]);
+                }
+            }
+        } else {
+            args.extend_from_slice(&["--", "-D", "warnings"]);
+        }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo clippy", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo clippy",
+            RunOptions {
+                background: self.background.unwrap_or(false),
+                timeout_secs,
+                format: OutputFormat::Lints,
+                limits,
+                invocation,
+                toolchain,
+            },
+        )
     }
 }
@@ -0,0 +1,213 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, execute_or_spawn, fit_run_output,
+    run_in_foreground,
+};
+use crate::tools::compile_options::{CompileOptions, Features};
+use crate::tools::diagnostics::MESSAGE_FORMAT_JSON;
+use crate::tools::metadata::Metadata;
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Flags that make rustdoc write JSON instead of HTML. Only nightly rustdoc accepts them.
+const RUSTDOC_JSON_FLAGS: &str = "-Z unstable-options --output-format json";
+
+/// Build documentation for the project and its dependencies, to read offline with doc_search
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_doc")]
+pub struct CargoDoc {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    /// Only document the project's own packages, not its dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_deps: Option<bool>,
+
+    /// Also document private items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub document_private_items: Option<bool>,
+
+    /// Write rustdoc JSON instead of HTML, which gives doc_search complete item signatures.
+    /// Needs a nightly toolchain, which is used unless another toolchain is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub json: Option<bool>,
+
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub background: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoDoc {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Document the project and all of its dependencies",
+                item: Self::default(),
+            },
+            Example {
+                description: "Document only one workspace member, with a feature enabled",
+                item: Self {
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        features: Some(Features::Joined("serde".into())),
+                        ..CompileOptions::default()
+                    },
+                    no_deps: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Generate rustdoc JSON with nightly for more precise doc_search results",
+                item: Self {
+                    json: Some(true),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoDoc {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_doc", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+        let json = self.json.unwrap_or(false);
+
+        // Use toolchain from args, session default, or none. JSON output needs nightly.
+        let toolchain = self
+            .toolchain
+            .or_else(|| json.then(|| "nightly".to_string()))
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let mut args = vec!["doc", MESSAGE_FORMAT_JSON];
+
+        let compile_args = self.compile.args();
+        args.extend(compile_args.iter().map(String::as_str));
+
+        if self.no_deps.unwrap_or(false) {
+            args.push("--no-deps");
+        }
+
+        if self.document_private_items.unwrap_or(false) {
+            args.push("--document-private-items");
+        }
+
+        let mut cargo_env = self.cargo_env.unwrap_or_default();
+        if json {
+            let flags = match cargo_env
+                .get("RUSTDOCFLAGS")
+                .cloned()
+                .or_else(|| std::env::var("RUSTDOCFLAGS").ok())
+            {
+                Some(flags) => format!("{flags} {RUSTDOC_JSON_FLAGS}"),
+                None => RUSTDOC_JSON_FLAGS.to_string(),
+            };
+            cargo_env.insert("RUSTDOCFLAGS".into(), flags);
+        }
+
+        let options = RunOptions {
+            background: self.background.unwrap_or(false),
+            timeout_secs,
+            format: OutputFormat::Diagnostics,
+            limits,
+            invocation,
+            toolchain,
+        };
+
+        // cargo knows where the target directory is, including one set in .cargo/config.
+        // Documentation for another platform goes in its own directory under it.
+        let metadata_options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation: options.invocation.clone(),
+            toolchain: options.toolchain.clone(),
+            ..RunOptions::default()
+        };
+        let metadata = Metadata::load(
+            state,
+            &project_path,
+            Some(&cargo_env),
+            true,
+            &metadata_options,
+        )?;
+        let doc_dir = match &self.compile.target {
+            Some(target) => metadata.target_directory.join(target).join("doc"),
+            None => metadata.target_directory.join("doc"),
+        };
+        let cmd = create_cargo_command(&args, options.toolchain.as_deref(), Some(&cargo_env));
+        if options.background {
+            let result = execute_or_spawn(state, cmd, &project_path, "cargo doc", options)?;
+            return Ok(format!(
+                "{result}\n📚 Documentation will be written to {}\n",
+                doc_dir.display()
+            ));
+        }
+
+        let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+        let rendered = options.format.render(&output, "cargo doc", &project_path);
+        let mut result = fit_run_output(&rendered, limits, &full_output, &output);
+        if output.timed_out.is_some() || output.cancelled {
+            bail!(result);
+        }
+        if output.succeeded() {
+            result.push_str(&format!(
+                "\n📚 Documentation is in {}. Search it with doc_search.\n",
+                doc_dir.display()
+            ));
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,403 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{RunOptions, create_cargo_command, fit_output, run_in_foreground};
+use crate::tools::diagnostics::{MESSAGE_FORMAT_JSON, parse_messages, summarize};
+use crate::tools::feature_matrix::{Combination, combinations};
+use crate::tools::metadata::Metadata;
+use crate::tools::test_results::{TestOutcome, parse_test_output};
+use anyhow::{Result, anyhow, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Write};
+
+/// How many combinations a run checks at most, unless told otherwise
+const DEFAULT_MAX_COMBINATIONS: usize = 100;
+
+/// Check a package with each of its features on its own, with no features, with all features
+/// and optionally with every combination of features, reporting which combinations fail
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_feature_matrix")]
+pub struct CargoFeatureMatrix {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Package whose features to check (defaults to the package at path)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Cargo command to run for each combination: "check" (default), "clippy", "build" or
+    /// "test"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub command: Option<String>,
+
+    /// Also try every combination of up to this many features (default: 1, each feature on
+    /// its own). The number of combinations grows quickly with depth.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub depth: Option<usize>,
+
+    /// Refuse to run more than this many combinations (default: 100, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_combinations: Option<usize>,
+
+    /// Features to leave out of the individual and combined feature runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub skip: Option<Vec<String>>,
+
+    /// Feature sets that must not be enabled together, e.g. "std,no_std". Combinations that
+    /// include all features of a set are not run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub exclude: Option<Vec<String>>,
+
+    /// Compile tests, examples and benches too
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_targets: Option<bool>,
+
+    /// Stop at the first combination that fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub fail_fast: Option<bool>,
+
+    /// Number of errors to show for each failing combination (default: 3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_errors: Option<usize>,
+
+    /// Optional timeout in seconds for each combination, after which cargo and everything it
+    /// started is killed (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+/// The outcome of running cargo with one combination of features
+#[derive(Debug, Serialize)]
+struct MatrixRow {
+    combination: Combination,
+    passed: bool,
+    errors: usize,
+    warnings: usize,
+    seconds: f64,
+    /// Diagnostic counts by level, e.g. "2 errors, 1 warning"
+    #[serde(skip)]
+    summary: String,
+    /// The first errors, rendered as text
+    #[serde(skip)]
+    first_errors: Vec<String>,
+    #[serde(skip)]
+    command: String,
+    #[serde(skip)]
+    full_output: String,
+}
+
+impl WithExamples for CargoFeatureMatrix {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Check each feature on its own, and with no or all features",
+                item: Self::default(),
+            },
+            Example {
+                description: "Check every pair of features of one package, including its tests",
+                item: Self {
+                    package: Some("my-lib".into()),
+                    depth: Some(2),
+                    all_targets: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Run clippy per feature, skipping a nightly-only feature and a \
+                              conflicting pair",
+                item: Self {
+                    command: Some("clippy".into()),
+                    skip: Some(vec!["nightly".into()]),
+                    exclude: Some(vec!["std,no_std".into()]),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoFeatureMatrix {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_feature_matrix", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let command = self.command.as_deref().unwrap_or("check");
+        if !matches!(command, "check" | "clippy" | "build" | "test") {
+            bail!("Unsupported command {command:?}: use check, clippy, build or test");
+        }
+
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain: toolchain.clone(),
+            ..RunOptions::default()
+        };
+        let metadata = Metadata::load(
+            state,
+            &project_path,
+            self.cargo_env.as_ref(),
+            true,
+            &options,
+        )?;
+        let package = match &self.package {
+            Some(name) => metadata
+                .packages
+                .iter()
+                .find(|package| package.name == *name && metadata.is_member(package))
+                .ok_or_else(|| anyhow!("No workspace member named {name}"))?,
+            None => metadata
+                .package_for_file(&project_path.join("Cargo.toml"))
+                .ok_or_else(|| {
+                    let members = metadata
+                        .packages
+                        .iter()
+                        .filter(|package| metadata.is_member(package))
+                        .map(|package| package.name.as_str())
+                        .collect::<Vec<_>>();
+                    anyhow!(
+                        "{} is a virtual workspace; choose a package: {}",
+                        project_path.display(),
+                        members.join(", ")
+                    )
+                })?,
+        };
+
+        let features = package.features.keys().cloned().collect::<Vec<_>>();
+        let exclude = self
+            .exclude
+            .iter()
+            .flatten()
+            .map(|set| {
+                set.split([',', '+', ' '])
+                    .filter(|feature| !feature.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let depth = self.depth.unwrap_or(1);
+        let max_combinations = self.max_combinations.unwrap_or(DEFAULT_MAX_COMBINATIONS);
+        let combinations = combinations(
+            &features,
+            self.skip.as_deref().unwrap_or_default(),
+            &exclude,
+            depth,
+            max_combinations,
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "{} has more than {max_combinations} feature combinations up to depth {depth}. \
+                 Lower depth, skip features or exclude sets of them, or raise max_combinations.",
+                package.name
+            )
+        })?;
+
+        let mut base_args = vec![command, MESSAGE_FORMAT_JSON, "--package", &package.name];
+        if self.all_targets.unwrap_or(false) {
+            base_args.push("--all-targets");
+        }
+        if command == "test" {
+            base_args.push("--no-fail-fast");
+        }
+
+        let max_errors = self.max_errors.unwrap_or(3);
+        let mut rows = Vec::new();
+        let mut stopped = None;
+        let mut interrupted = false;
+        for combination in combinations {
+            let combination_args = combination.args();
+            let mut args = base_args.clone();
+            args.extend(combination_args.iter().map(String::as_str));
+            let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
+            let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+
+            let (diagnostics, _) = parse_messages(&output.stdout);
+            let errors = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.level.starts_with("error"))
+                .collect::<Vec<_>>();
+            let mut first_errors = errors
+                .iter()
+                .take(max_errors)
+                .map(|diagnostic| {
+                    let mut rendered = String::new();
+                    diagnostic.render(&mut rendered);
+                    rendered
+                })
+                .collect::<Vec<_>>();
+            if first_errors.is_empty() && command == "test" {
+                // It compiled, so the failures are in the tests themselves
+                first_errors = parse_test_output(&output.stdout, &output.stderr)
+                    .iter()
+                    .flat_map(|binary| &binary.tests)
+                    .filter(|test| test.outcome == TestOutcome::Failed)
+                    .take(max_errors)
+                    .map(|test| match &test.panic {
+                        Some(panic) => format!("test {} failed: {}\n", test.name, panic.message),
+                        None => format!("test {} failed\n", test.name),
+                    })
+                    .collect();
+            }
+            if first_errors.is_empty() && !output.succeeded() {
+                let tail = output.stderr.lines().rev().take(5).collect::<Vec<_>>();
+                first_errors.push(
+                    tail.into_iter()
+                        .rev()
+                        .map(|line| format!("{line}\n"))
+                        .collect(),
+                );
+            }
+
+            let passed = output.succeeded();
+            rows.push(MatrixRow {
+                combination,
+                passed,
+                errors: errors.len(),
+                warnings: diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.level == "warning")
+                    .count(),
+                seconds: (output.duration.as_secs_f64() * 1000.0).round() / 1000.0,
+                summary: summarize(&diagnostics),
+                first_errors,
+                command: output.command.clone(),
+                full_output,
+            });
+
+            if output.cancelled {
+                stopped = Some("🛑 Cancelled; the remaining combinations were not run".into());
+                interrupted = true;
+                break;
+            }
+            if let Some(timeout_secs) = output.timed_out {
+                stopped = Some(format!(
+                    "⏱️  Timed out after {timeout_secs} seconds; the remaining combinations \
+                     were not run"
+                ));
+                interrupted = true;
+                break;
+            }
+            if !passed && self.fail_fast.unwrap_or(false) {
+                stopped = Some("⏹️  Stopped at the first failure (fail_fast)".into());
+                break;
+            }
+        }
+
+        let failed = rows.iter().filter(|row| !row.passed).count();
+        let mut result = format!(
+            "=== cargo feature matrix ===\n📁 Working directory: {}\n📦 Package: {} {} ({} \
+             feature{})\n🔧 Command: cargo {}\n\n",
+            project_path.display(),
+            package.name,
+            package.version,
+            features.len(),
+            if features.len() == 1 { "" } else { "s" },
+            base_args.join(" ")
+        );
+        if failed == 0 {
+            let _ = writeln!(result, "✅ All {} combinations passed", rows.len());
+        } else {
+            let _ = writeln!(result, "❌ {failed} of {} combinations failed", rows.len());
+        }
+        if let Some(stopped) = stopped {
+            let _ = writeln!(result, "{stopped}");
+        }
+
+        let width = rows
+            .iter()
+            .map(|row| row.combination.to_string().chars().count())
+            .max()
+            .unwrap_or(0);
+        result.push_str("\n📊 Matrix:\n");
+        for row in &rows {
+            let (mark, status) = if row.passed {
+                ("✅", "passed")
+            } else {
+                ("❌", "failed")
+            };
+            let _ = writeln!(
+                result,
+                "   {mark} {:width$}  {status} in {:.1}s ({})",
+                row.combination.to_string(),
+                row.seconds,
+                row.summary,
+            );
+        }
+
+        for row in rows.iter().filter(|row| !row.passed) {
+            let _ = writeln!(result, "\n❌ {}: {}", row.combination, row.command);
+            for error in &row.first_errors {
+                result.push_str(error);
+            }
+            if row.errors > row.first_errors.len() {
+                let _ = writeln!(
+                    result,
+                    "… and {} more errors",
+                    row.errors - row.first_errors.len()
+                );
+            }
+            let _ = writeln!(result, "📜 Full log: {}", row.full_output);
+        }
+
+        result.push_str("\n🧾 Structured matrix (JSON):\n");
+        result.push_str(&serde_json::to_string(&rows).unwrap_or_default());
+        result.push('\n');
+
+        let result = fit_output(&result, limits, "list_runs and get_run_output");
+        if interrupted {
+            bail!(result);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::project::find_workspace_root;
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, fit_run_output, run_in_foreground,
+};
+use crate::tools::compile_options::{CompileOptions, TargetSelection};
+use crate::tools::diagnostics::MESSAGE_FORMAT_JSON;
+use crate::tools::file_changes::{changes_since, push_changes, rust_files, snapshot};
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Apply machine-applicable compiler (and optionally clippy) suggestions with cargo fix, and
+/// report which files were changed
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_fix")]
+pub struct CargoFix {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub targets: TargetSelection,
+
+    /// Also apply clippy's suggestions, by running `cargo clippy --fix` instead of `cargo fix`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub clippy: Option<bool>,
+
+    /// Migrate the code to the next edition. Update the edition in Cargo.toml afterwards.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub edition: Option<bool>,
+
+    /// Apply the idiom lints of the package's current edition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub edition_idioms: Option<bool>,
+
+    /// Keep fixes even if they leave the code failing to compile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub broken_code: Option<bool>,
+
+    /// Fix files even if they have uncommitted changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub allow_dirty: Option<bool>,
+
+    /// Fix files even if they have staged changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub allow_staged: Option<bool>,
+
+    /// Fix files even if the project is not under version control
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub allow_no_vcs: Option<bool>,
+
+    /// Include a unified diff of the fixes (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub diff: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoFix {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Apply the compiler's suggestions, even with uncommitted changes",
+                item: Self {
+                    allow_dirty: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Apply compiler and clippy suggestions across the workspace",
+                item: Self {
+                    targets: TargetSelection {
+                        workspace: Some(true),
+                        ..TargetSelection::default()
+                    },
+                    clippy: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Migrate a package to the next edition",
+                item: Self {
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        ..CompileOptions::default()
+                    },
+                    edition: Some(true),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoFix {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_fix", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+        let clippy = self.clippy.unwrap_or(false);
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let mut args = if clippy {
+            vec!["clippy", "--fix"]
+        } else {
+            vec!["fix"]
+        };
+        args.push(MESSAGE_FORMAT_JSON);
+
+        let compile_args = self.compile.args();
+        let target_args = self.targets.args();
+        args.extend(compile_args.iter().map(String::as_str));
+        args.extend(target_args.iter().map(String::as_str));
+
+        if self.edition.unwrap_or(false) {
+            args.push("--edition");
+        }
+
+        if self.edition_idioms.unwrap_or(false) {
+            args.push("--edition-idioms");
+        }
+
+        if self.broken_code.unwrap_or(false) {
+            args.push("--broken-code");
+        }
+
+        if self.allow_dirty.unwrap_or(false) {
+            args.push("--allow-dirty");
+        }
+
+        if self.allow_staged.unwrap_or(false) {
+            args.push("--allow-staged");
+        }
+
+        if self.allow_no_vcs.unwrap_or(false) {
+            args.push("--allow-no-vcs");
+        }
+
+        // cargo fix rewrites files in place without saying which, so compare every source
+        // file in the workspace before and after
+        let workspace_root = find_workspace_root(&project_path);
+        let before = snapshot(rust_files(&workspace_root));
+
+        let command_name = if clippy {
+            "cargo clippy --fix"
+        } else {
+            "cargo fix"
+        };
+        let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
+        let options = RunOptions {
+            timeout_secs,
+            format: OutputFormat::Diagnostics,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+        let changes = changes_since(before);
+
+        let mut result = options.format.render(&output, command_name, &project_path);
+        if changes.is_empty() {
+            result.push_str("ℹ️  No files were changed\n");
+        } else {
+            push_changes(
+                &mut result,
+                "📝 Fixed",
+                &changes,
+                &workspace_root,
+                self.diff.unwrap_or(true),
+            );
+        }
+
+        let result = fit_run_output(&result, limits, &full_output, &output);
+        if output.timed_out.is_some() || output.cancelled {
+            bail!(result);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,356 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, create_toolchain_command, fit_output,
+    fit_run_output, format_command, push_section, run_in_foreground,
+};
+use crate::tools::file_changes::{FileChange, changes_since, push_changes, snapshot};
+use crate::tools::metadata::Metadata;
+use crate::tools::rustfmt_output::{listed_files, split_emitted};
+use anyhow::{Result, anyhow, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    process::Command,
+};
+
+/// Format code with rustfmt, reporting which files changed along with a unified diff
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_fmt")]
+pub struct CargoFmt {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Optional package to format (for workspaces)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Format every workspace member and their local path dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all: Option<bool>,
+
+    /// Only format these files (relative to the project directory), with the edition of the
+    /// package each belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub files: Option<Vec<String>>,
+
+    /// Only report what would change, without modifying any file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub check: Option<bool>,
+
+    /// Include a unified diff of the changes (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub diff: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoFmt {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Format the current project",
+                item: Self::default(),
+            },
+            Example {
+                description: "Show what formatting would change in one package, without writing",
+                item: Self {
+                    package: Some("my-lib".into()),
+                    check: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Format only the files that were just edited",
+                item: Self {
+                    files: Some(vec!["src/main.rs".into(), "src/tools/parser.rs".into()]),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+/// Files to format, grouped by the edition to format them with (`None` leaves it to rustfmt)
+type FilesByEdition = BTreeMap<Option<String>, Vec<PathBuf>>;
+
+impl CargoFmt {
+    fn group_by_edition(&self, files: &[PathBuf], metadata: &Metadata) -> FilesByEdition {
+        let mut groups = FilesByEdition::new();
+        for file in files {
+            let edition = metadata
+                .package_for_file(file)
+                .map(|package| package.edition.clone());
+            groups.entry(edition).or_default().push(file.clone());
+        }
+        groups
+    }
+
+    fn rustfmt_command(
+        &self,
+        edition: Option<&str>,
+        flags: &[&str],
+        files: &[PathBuf],
+        toolchain: Option<&str>,
+    ) -> Command {
+        let mut args = flags.to_vec();
+        if let Some(edition) = edition {
+            args.extend_from_slice(&["--edition", edition]);
+        }
+        let mut cmd =
+            create_toolchain_command("rustfmt", &args, toolchain, self.cargo_env.as_ref());
+        cmd.args(files);
+        cmd
+    }
+
+    fn cargo_fmt_command(&self, flags: &[&str], toolchain: Option<&str>) -> Command {
+        let mut args = vec!["fmt"];
+        if let Some(ref package) = self.package {
+            args.extend_from_slice(&["--package", package]);
+        }
+        if self.all.unwrap_or(false) {
+            args.push("--all");
+        }
+        args.extend_from_slice(flags);
+        create_cargo_command(&args, toolchain, self.cargo_env.as_ref())
+    }
+}
+
+impl Tool<CargoTools> for CargoFmt {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_fmt", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+        let check = self.check.unwrap_or(false);
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain: toolchain.clone(),
+            ..RunOptions::default()
+        };
+
+        // rustfmt is run directly on individual files, and to preview changes, so it needs to
+        // be told each package's edition the way cargo fmt would
+        let given_files = self.files.as_ref().map(|files| {
+            files
+                .iter()
+                .map(|file| project_path.join(&*shellexpand::tilde(file)))
+                .collect::<Vec<_>>()
+        });
+        let metadata = if given_files.is_some() || check {
+            Some(Metadata::load(
+                state,
+                &project_path,
+                self.cargo_env.as_ref(),
+                true,
+                &options,
+            )?)
+        } else {
+            None
+        };
+        let groups = |files: &[PathBuf]| {
+            metadata
+                .as_ref()
+                .map(|metadata| self.group_by_edition(files, metadata))
+                .unwrap_or_default()
+        };
+
+        // First find out which files need formatting
+        let mut listing = Vec::new();
+        match &given_files {
+            Some(files) => {
+                for file in files {
+                    if !file.is_file() {
+                        bail!("Cannot format {}: no such file", file.display());
+                    }
+                }
+                for (edition, files) in groups(files) {
+                    let cmd = self.rustfmt_command(
+                        edition.as_deref(),
+                        &["--check", "-l"],
+                        &files,
+                        toolchain.as_deref(),
+                    );
+                    listing.push(run_in_foreground(state, cmd, &project_path, &options)?);
+                }
+            }
+            None => {
+                let cmd = self.cargo_fmt_command(
+                    &["--check", "--message-format", "short"],
+                    toolchain.as_deref(),
+                );
+                listing.push(run_in_foreground(state, cmd, &project_path, &options)?);
+            }
+        }
+
+        let mut unformatted = Vec::new();
+        let mut errors = String::new();
+        for (output, full_output) in &listing {
+            let files = listed_files(&output.stdout, &project_path);
+            if output.timed_out.is_some() || output.cancelled {
+                let rendered = OutputFormat::Plain.render(output, "cargo fmt", &project_path);
+                bail!(fit_run_output(&rendered, limits, full_output, output));
+            }
+            if !output.status.success() && files.is_empty() {
+                // Nothing listed, so it failed for another reason, such as a syntax error
+                let rendered = OutputFormat::Plain.render(output, "cargo fmt", &project_path);
+                return Ok(fit_run_output(&rendered, limits, full_output, output));
+            }
+            errors.push_str(&output.stderr);
+            unformatted.extend(files);
+        }
+        unformatted.sort();
+        unformatted.dedup();
+
+        let (last_output, mut full_output) =
+            listing.pop().ok_or_else(|| anyhow!("No files to format"))?;
+        let mut command = last_output.command.clone();
+
+        let changes = if unformatted.is_empty() {
+            Vec::new()
+        } else if check {
+            // Preview the formatted files on stdout
+            let mut changes = Vec::new();
+            let before = snapshot(unformatted.clone());
+            for (edition, files) in groups(&unformatted) {
+                let cmd = self.rustfmt_command(
+                    edition.as_deref(),
+                    &["--emit", "stdout"],
+                    &files,
+                    toolchain.as_deref(),
+                );
+                let (output, full) = run_in_foreground(state, cmd, &project_path, &options)?;
+                if !output.succeeded() {
+                    let rendered = OutputFormat::Plain.render(&output, "cargo fmt", &project_path);
+                    bail!(fit_run_output(&rendered, limits, &full, &output));
+                }
+                let formatted = split_emitted(&output.stdout, &files);
+                for file in files {
+                    if let (Some(before), Some(after)) = (before.get(&file), formatted.get(&file))
+                        && before != after
+                    {
+                        changes.push(FileChange {
+                            path: file,
+                            before: before.clone(),
+                            after: after.clone(),
+                        });
+                    }
+                }
+            }
+            changes
+        } else {
+            let before = snapshot(unformatted.clone());
+            let mut commands = Vec::new();
+            match &given_files {
+                Some(_) => {
+                    for (edition, files) in groups(&unformatted) {
+                        commands.push(self.rustfmt_command(
+                            edition.as_deref(),
+                            &[],
+                            &files,
+                            toolchain.as_deref(),
+                        ));
+                    }
+                }
+                None => commands.push(self.cargo_fmt_command(&[], toolchain.as_deref())),
+            }
+            for cmd in commands {
+                command = format_command(&cmd);
+                let (output, full) = run_in_foreground(state, cmd, &project_path, &options)?;
+                full_output = full;
+                if !output.succeeded() {
+                    let rendered = OutputFormat::Plain.render(&output, "cargo fmt", &project_path);
+                    let rendered = fit_run_output(&rendered, limits, &full_output, &output);
+                    if output.timed_out.is_some() || output.cancelled {
+                        bail!(rendered);
+                    }
+                    return Ok(rendered);
+                }
+                errors.push_str(&output.stderr);
+            }
+            changes_since(before)
+        };
+
+        let mut result = format!(
+            "=== cargo fmt ===\n📁 Working directory: {}\n🔧 Command: {command}\n\n",
+            project_path.display()
+        );
+        if changes.is_empty() {
+            result.push_str("✅ All files are formatted\n\n");
+        } else if check {
+            result.push_str("❌ Some files are not formatted; nothing was changed\n\n");
+        } else {
+            result.push_str("✅ Formatting applied\n\n");
+        }
+        push_section(&mut result, "📤 STDERR:", &errors);
+        if !changes.is_empty() {
+            let label = if check {
+                "📝 Would reformat"
+            } else {
+                "📝 Reformatted"
+            };
+            push_changes(
+                &mut result,
+                label,
+                &changes,
+                &project_path,
+                self.diff.unwrap_or(true),
+            );
+        }
+
+        Ok(fit_output(&result, limits, &full_output))
+    }
+}
@@ -1,5 +1,6 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{RunOptions, create_cargo_command, execute_or_spawn};
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -12,6 +13,29 @@ use std::collections::HashMap;
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_fmt_check")]
 pub struct CargoFmtCheck {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -29,6 +53,10 @@ impl WithExamples for CargoFmtCheck {
             Example {
                 description: "Check formatting in current project",
                 item: Self {
+                    path: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -36,6 +64,10 @@ impl WithExamples for CargoFmtCheck {
             Example {
                 description: "Check formatting with nightly toolchain",
                 item: Self {
+                    path: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: Some("nightly".into()),
                     cargo_env: None,
                 },
@@ -46,15 +78,36 @@ impl WithExamples for CargoFmtCheck {
 
 impl Tool<CargoTools> for CargoFmtCheck {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_fmt_check", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let args = vec!["fmt", "--check"];
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo fmt --check", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo fmt --check",
+            RunOptions {
+                timeout_secs,
+                limits,
+                invocation,
+                toolchain,
+                ..RunOptions::default()
+            },
+        )
     }
 }
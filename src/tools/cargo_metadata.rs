@@ -0,0 +1,455 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, fit_run_output, push_structured,
+    run_in_foreground,
+};
+use crate::tools::compile_options::Features;
+use crate::tools::metadata::{Metadata, Package};
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Describe the workspace: its members, their targets, features and resolved dependencies
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_metadata")]
+pub struct CargoMetadata {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Only describe this package (a workspace member or, with all_packages, any dependency)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Describe every package in the dependency graph, not just workspace members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_packages: Option<bool>,
+
+    /// Skip dependency resolution, which is faster but shows version requirements rather than
+    /// resolved versions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_deps: Option<bool>,
+
+    /// Features to activate when resolving, as a list or a space- or comma-separated string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub features: Option<Features>,
+
+    /// Activate all features when resolving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_features: Option<bool>,
+
+    /// Only include dependencies used on this target triple
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub filter_platform: Option<String>,
+
+    /// Include each package's targets (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub show_targets: Option<bool>,
+
+    /// Include each package's features (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub show_features: Option<bool>,
+
+    /// Include each package's dependencies (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub show_dependencies: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoMetadata {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Describe all workspace members",
+                item: Self::default(),
+            },
+            Example {
+                description: "List the targets of each member, without resolving dependencies",
+                item: Self {
+                    no_deps: Some(true),
+                    show_features: Some(false),
+                    show_dependencies: Some(false),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Show which version of a dependency was resolved and its features",
+                item: Self {
+                    package: Some("serde".into()),
+                    all_packages: Some(true),
+                    show_targets: Some(false),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+/// Condensed view of `cargo metadata`
+#[derive(Debug, Serialize)]
+struct WorkspaceSummary {
+    workspace_root: PathBuf,
+    target_directory: PathBuf,
+    packages: Vec<PackageSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageSummary {
+    name: String,
+    version: String,
+    member: bool,
+    manifest_path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    targets: Option<Vec<TargetSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    features: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled_features: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependencies: Option<Vec<DependencySummary>>,
+}
+
+#[derive(Debug, Serialize)]
+struct TargetSummary {
+    name: String,
+    kind: Vec<String>,
+    src_path: PathBuf,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    required_features: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DependencySummary {
+    name: String,
+    req: String,
+    /// The version it resolved to, unless resolution was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolved: Option<String>,
+    kind: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    no_default_features: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+}
+
+impl CargoMetadata {
+    fn summarize_package(&self, metadata: &Metadata, package: &Package) -> PackageSummary {
+        let package_dir = package.manifest_path.parent().unwrap_or(Path::new(""));
+
+        let targets = self.show_targets.unwrap_or(true).then(|| {
+            package
+                .targets
+                .iter()
+                .map(|target| TargetSummary {
+                    name: target.name.clone(),
+                    kind: target.kind.clone(),
+                    src_path: target
+                        .src_path
+                        .strip_prefix(package_dir)
+                        .unwrap_or(&target.src_path)
+                        .to_path_buf(),
+                    required_features: target.required_features.clone(),
+                })
+                .collect()
+        });
+
+        let show_features = self.show_features.unwrap_or(true);
+        let features = show_features.then(|| package.features.clone());
+        let enabled_features = show_features
+            .then(|| metadata.node(&package.id).map(|node| node.features.clone()))
+            .flatten();
+
+        let dependencies = self.show_dependencies.unwrap_or(true).then(|| {
+            package
+                .dependencies
+                .iter()
+                .map(|dependency| DependencySummary {
+                    name: dependency.name.clone(),
+                    req: dependency.req.clone(),
+                    resolved: metadata
+                        .resolved_dependency(package, dependency)
+                        .map(|resolved| resolved.version.clone()),
+                    kind: dependency.kind_name().to_string(),
+                    optional: dependency.optional,
+                    no_default_features: !dependency.uses_default_features,
+                    features: dependency.features.clone(),
+                    target: dependency.target.clone(),
+                })
+                .collect()
+        });
+
+        PackageSummary {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            member: metadata.is_member(package),
+            manifest_path: package.manifest_path.clone(),
+            source: package.source.clone(),
+            targets,
+            features,
+            enabled_features,
+            dependencies,
+        }
+    }
+}
+
+fn render_package(out: &mut String, package: &PackageSummary, workspace_root: &Path) {
+    let location = package
+        .manifest_path
+        .parent()
+        .map(|dir| dir.strip_prefix(workspace_root).unwrap_or(dir))
+        .map(|dir| dir.display().to_string())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or_else(|| ".".to_string());
+    let _ = write!(out, "📦 {} {}", package.name, package.version);
+    if package.member {
+        let _ = writeln!(out, " ({location})");
+    } else {
+        let _ = writeln!(out, " ({})", package.source.as_deref().unwrap_or(&location));
+    }
+
+    if let Some(targets) = &package.targets {
+        let targets = targets
+            .iter()
+            .map(|target| format!("{} {}", target.kind.join("/"), target.name))
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "   🎯 Targets: {}", targets.join(", "));
+    }
+
+    if let Some(features) = &package.features
+        && !features.is_empty()
+    {
+        let features = features
+            .iter()
+            .map(|(name, implies)| {
+                if implies.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{name} → [{}]", implies.join(", "))
+                }
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "   🚩 Features: {}", features.join("; "));
+    }
+    if let Some(enabled) = &package.enabled_features
+        && !enabled.is_empty()
+    {
+        let _ = writeln!(out, "   ✅ Enabled: {}", enabled.join(", "));
+    }
+
+    if let Some(dependencies) = &package.dependencies
+        && !dependencies.is_empty()
+    {
+        let _ = writeln!(out, "   🔗 Dependencies:");
+        for dependency in dependencies {
+            let _ = write!(out, "      {} {}", dependency.name, dependency.req);
+            if let Some(resolved) = &dependency.resolved {
+                let _ = write!(out, " → {resolved}");
+            }
+
+            let mut notes = Vec::new();
+            if dependency.kind != "normal" {
+                notes.push(dependency.kind.clone());
+            }
+            if dependency.optional {
+                notes.push("optional".into());
+            }
+            if dependency.no_default_features {
+                notes.push("no default features".into());
+            }
+            if !dependency.features.is_empty() {
+                notes.push(format!("features: {}", dependency.features.join(", ")));
+            }
+            if let Some(target) = &dependency.target {
+                notes.push(format!("only on {target}"));
+            }
+            if !notes.is_empty() {
+                let _ = write!(out, " ({})", notes.join("; "));
+            }
+            out.push('\n');
+        }
+    }
+    out.push('\n');
+}
+
+impl Tool<CargoTools> for CargoMetadata {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_metadata", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let mut args = vec!["metadata", "--format-version", "1"];
+
+        if self.no_deps.unwrap_or(false) {
+            args.push("--no-deps");
+        }
+
+        let features_str;
+        let features = self.features.as_ref().map(Features::names);
+        if let Some(features) = features
+            && !features.is_empty()
+        {
+            features_str = features.join(",");
+            args.extend_from_slice(&["--features", &features_str]);
+        }
+
+        if self.all_features.unwrap_or(false) {
+            args.push("--all-features");
+        }
+
+        if let Some(ref platform) = self.filter_platform {
+            args.extend_from_slice(&["--filter-platform", platform]);
+        }
+
+        let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+        if !output.succeeded() {
+            let result = OutputFormat::Plain.render(&output, "cargo metadata", &project_path);
+            bail!(fit_run_output(&result, limits, &full_output, &output));
+        }
+
+        let metadata = Metadata::parse(&output.stdout)?;
+        let all_packages = self.all_packages.unwrap_or(false);
+        let packages = metadata
+            .packages
+            .iter()
+            .filter(|package| all_packages || metadata.is_member(package))
+            .filter(|package| {
+                self.package
+                    .as_ref()
+                    .is_none_or(|name| &package.name == name)
+            })
+            .map(|package| self.summarize_package(&metadata, package))
+            .collect::<Vec<_>>();
+
+        if packages.is_empty()
+            && let Some(name) = &self.package
+        {
+            bail!(
+                "No package named {name} in {}{}",
+                if all_packages {
+                    "the dependency graph"
+                } else {
+                    "the workspace members"
+                },
+                if all_packages {
+                    ""
+                } else {
+                    " (set all_packages to search dependencies)"
+                }
+            );
+        }
+
+        let summary = WorkspaceSummary {
+            workspace_root: metadata.workspace_root.clone(),
+            target_directory: metadata.target_directory.clone(),
+            packages,
+        };
+
+        let mut result = output.header("cargo metadata", &project_path);
+        let _ = writeln!(
+            result,
+            "🏠 Workspace root: {}\n🎯 Target directory: {}\n",
+            summary.workspace_root.display(),
+            summary.target_directory.display()
+        );
+
+        let members = summary
+            .packages
+            .iter()
+            .filter(|package| package.member)
+            .count();
+        let _ = writeln!(
+            result,
+            "📋 {} package{} ({members} workspace member{})\n",
+            summary.packages.len(),
+            if summary.packages.len() == 1 { "" } else { "s" },
+            if members == 1 { "" } else { "s" }
+        );
+
+        let mut packages = String::new();
+        for package in &summary.packages {
+            render_package(&mut packages, package, &summary.workspace_root);
+        }
+        push_structured(
+            &mut result,
+            &packages,
+            "🧾 Structured metadata (JSON):",
+            &summary,
+            limits,
+        )?;
+
+        Ok(fit_run_output(&result, limits, &full_output, &output))
+    }
+}
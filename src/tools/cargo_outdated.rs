@@ -0,0 +1,342 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{RunOptions, fit_output};
+use crate::tools::metadata::Metadata;
+use crate::tools::registry_index::RegistryIndex;
+use anyhow::{Result, anyhow};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::PathBuf,
+};
+
+/// List direct dependencies with newer releases, comparing each workspace member's version
+/// requirements against the crates.io index cached by cargo (or a local index mirror), without
+/// network access
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_outdated")]
+pub struct CargoOutdated {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Only check this workspace member (default: every member)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Directory of a local crates.io index mirror to read instead of cargo's index cache
+    /// (defaults to CARGO_MCP_REGISTRY_INDEX, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub index_path: Option<String>,
+
+    /// Also list dependencies that are up to date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+/// How a direct dependency compares with the newest releases in the index
+#[derive(Debug, Serialize)]
+struct OutdatedDependency {
+    member: String,
+    name: String,
+    /// "normal", "dev" or "build"
+    kind: String,
+    req: String,
+    /// The version in Cargo.lock, if the dependency is resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<String>,
+    /// The newest release the requirement allows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compatible: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+    /// "up_to_date", "compatible" (`cargo update` reaches it), "breaking" (the requirement must
+    /// change) or "unknown" (not in the index)
+    status: &'static str,
+}
+
+impl WithExamples for CargoOutdated {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "List outdated dependencies of every workspace member",
+                item: Self::default(),
+            },
+            Example {
+                description: "Show every dependency of one member, including up-to-date ones",
+                item: Self {
+                    package: Some("my-lib".into()),
+                    all: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Compare against a local checkout of the crates.io index",
+                item: Self {
+                    index_path: Some("~/mirrors/crates.io-index".into()),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoOutdated {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_outdated", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let index_path = self
+            .index_path
+            .clone()
+            .or_else(|| std::env::var("CARGO_MCP_REGISTRY_INDEX").ok())
+            .map(|path| PathBuf::from(&*shellexpand::tilde(&path)));
+        let index = RegistryIndex::locate(index_path.as_deref())?;
+
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        let metadata = Metadata::load(
+            state,
+            &project_path,
+            self.cargo_env.as_ref(),
+            false,
+            &options,
+        )?;
+        let members = metadata
+            .packages
+            .iter()
+            .filter(|package| metadata.is_member(package))
+            .filter(|package| {
+                self.package
+                    .as_ref()
+                    .is_none_or(|name| package.name == *name)
+            })
+            .collect::<Vec<_>>();
+        if let Some(name) = &self.package
+            && members.is_empty()
+        {
+            return Err(anyhow!("No workspace member named {name}"));
+        }
+
+        let mut dependencies = Vec::new();
+        let mut skipped = Vec::new();
+        for member in &members {
+            let mut seen = HashSet::new();
+            for dependency in &member.dependencies {
+                if !seen.insert((&dependency.name, dependency.kind_name(), &dependency.req)) {
+                    continue;
+                }
+                let from_crates_io = dependency.source.as_deref().is_some_and(|source| {
+                    source == "registry+https://github.com/rust-lang/crates.io-index"
+                        || source == "sparse+https://index.crates.io/"
+                });
+                if !from_crates_io {
+                    skipped.push(format!("{} ({})", dependency.name, member.name));
+                    continue;
+                }
+
+                let locked = metadata
+                    .resolved_dependency(member, dependency)
+                    .and_then(|package| Version::parse(&package.version).ok());
+                let req = VersionReq::parse(&dependency.req).ok();
+                let prerelease = locked.as_ref().is_some_and(|locked| !locked.pre.is_empty());
+                let versions = index.versions(&dependency.name, prerelease);
+
+                let latest = versions.as_ref().and_then(|versions| versions.first());
+                let compatible = versions.as_ref().and_then(|versions| {
+                    versions
+                        .iter()
+                        .find(|version| req.as_ref().is_some_and(|req| req.matches(version)))
+                });
+                let status = match (latest, &locked) {
+                    (None, _) => "unknown",
+                    (Some(latest), _) if req.as_ref().is_some_and(|req| !req.matches(latest)) => {
+                        "breaking"
+                    }
+                    (Some(latest), Some(locked)) if latest > locked => "compatible",
+                    _ => "up_to_date",
+                };
+
+                dependencies.push(OutdatedDependency {
+                    member: member.name.clone(),
+                    name: dependency.name.clone(),
+                    kind: dependency.kind_name().into(),
+                    req: dependency.req.clone(),
+                    locked: locked.map(|version| version.to_string()),
+                    compatible: compatible.map(ToString::to_string),
+                    latest: latest.map(ToString::to_string),
+                    status,
+                });
+            }
+        }
+
+        let count = |status: &str| {
+            dependencies
+                .iter()
+                .filter(|dependency| dependency.status == status)
+                .count()
+        };
+        let (compatible, breaking, unknown) =
+            (count("compatible"), count("breaking"), count("unknown"));
+
+        let mut result = format!(
+            "=== cargo outdated ===\n📁 Working directory: {}\n🗂️  Index: {}\n",
+            project_path.display(),
+            index
+                .paths()
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if matches!(index, RegistryIndex::Cache(_)) {
+            result.push_str(
+                "ℹ️  Cargo's index cache only knows the releases published when cargo last \
+                 queried the index; run cargo_update to refresh it\n",
+            );
+        }
+
+        let all = self.all.unwrap_or(false);
+        for member in &members {
+            let rows = dependencies
+                .iter()
+                .filter(|dependency| dependency.member == member.name)
+                .filter(|dependency| all || dependency.status != "up_to_date")
+                .collect::<Vec<_>>();
+            if rows.is_empty() {
+                continue;
+            }
+            let _ = writeln!(result, "\n📦 {} {}:", member.name, member.version);
+            for row in rows {
+                let kind = if row.kind == "normal" {
+                    String::new()
+                } else {
+                    format!(" [{}]", row.kind)
+                };
+                let locked = row.locked.as_deref().unwrap_or("not locked");
+                let line = match row.status {
+                    "breaking" => format!(
+                        "💥 {}{kind} {} (locked {locked}): latest {} is breaking{}",
+                        row.name,
+                        row.req,
+                        row.latest.as_deref().unwrap_or_default(),
+                        match &row.compatible {
+                            Some(compatible) if row.locked.as_ref() != Some(compatible) =>
+                                format!(", compatible {compatible}"),
+                            _ => String::new(),
+                        }
+                    ),
+                    "compatible" => format!(
+                        "⬆️  {}{kind} {} (locked {locked}): compatible {}",
+                        row.name,
+                        row.req,
+                        row.compatible.as_deref().unwrap_or_default()
+                    ),
+                    "unknown" => format!(
+                        "❔ {}{kind} {} (locked {locked}): not in the index",
+                        row.name, row.req
+                    ),
+                    _ => format!("✅ {}{kind} {} (locked {locked})", row.name, row.req),
+                };
+                let _ = writeln!(result, "   {line}");
+            }
+        }
+
+        result.push('\n');
+        if compatible == 0 && breaking == 0 {
+            result.push_str("✅ All crates.io dependencies are up to date\n");
+        } else {
+            let _ = writeln!(
+                result,
+                "📊 {compatible} compatible update{}, {breaking} breaking update{}",
+                if compatible == 1 { "" } else { "s" },
+                if breaking == 1 { "" } else { "s" }
+            );
+            result.push_str(
+                "💡 cargo_update applies compatible updates; breaking ones need the version \
+                 requirement in Cargo.toml raised\n",
+            );
+        }
+        if unknown > 0 {
+            let _ = writeln!(
+                result,
+                "❔ {unknown} dependenc{} not found in the index",
+                if unknown == 1 { "y" } else { "ies" }
+            );
+        }
+        if !skipped.is_empty() {
+            let _ = writeln!(
+                result,
+                "ℹ️  Not from crates.io, so not checked: {}",
+                skipped.join(", ")
+            );
+        }
+
+        result.push_str("\n🧾 Structured dependencies (JSON):\n");
+        let listed = dependencies
+            .iter()
+            .filter(|dependency| all || dependency.status != "up_to_date")
+            .collect::<Vec<_>>();
+        result.push_str(&serde_json::to_string(&listed).unwrap_or_default());
+        result.push('\n');
+
+        Ok(fit_output(&result, limits, "a larger max_output_bytes"))
+    }
+}
@@ -0,0 +1,224 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{RunOptions, fit_output};
+use crate::tools::metadata::Metadata;
+use crate::tools::policy::{CHECKS, Policy, Violation};
+use anyhow::{Result, anyhow, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Write, fs};
+
+const EXAMPLE_POLICY: &str = r#"[licenses]
+allow = ["MIT", "Apache-2.0", "Unicode-3.0"]
+exceptions = [{ name = "ring", allow = ["OpenSSL"] }]
+
+[bans]
+multiple-versions = "warn"
+deny = ["openssl-sys", { name = "time", version = "<0.2" }]
+skip = ["windows-sys"]
+
+[sources]
+unknown-registry = "deny"
+unknown-git = "deny"
+allow-git = ["https://github.com/org/repo"]"#;
+
+/// Check the project's dependencies against a policy of allowed licenses, allowed sources,
+/// banned crates and duplicate versions, read from a cargo-deny style `deny.toml`
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_policy")]
+pub struct CargoPolicy {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Policy file, relative to the workspace root (default: "deny.toml")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub policy_file: Option<String>,
+
+    /// Checks to run: "licenses", "sources", "bans" and/or "duplicates" (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub checks: Option<Vec<String>>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoPolicy {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Check all dependencies against the project's deny.toml",
+                item: Self::default(),
+            },
+            Example {
+                description: "Only check licenses, using a policy kept elsewhere in the project",
+                item: Self {
+                    policy_file: Some(".config/deny.toml".into()),
+                    checks: Some(vec!["licenses".into()]),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoPolicy {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_policy", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let checks = match &self.checks {
+            Some(checks) => {
+                for check in checks {
+                    if !CHECKS.contains(&check.as_str()) {
+                        bail!("Unknown check {check:?}: use {}", CHECKS.join(", "));
+                    }
+                }
+                checks.iter().map(String::as_str).collect::<Vec<_>>()
+            }
+            None => CHECKS.to_vec(),
+        };
+
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        let metadata = Metadata::load(
+            state,
+            &project_path,
+            self.cargo_env.as_ref(),
+            false,
+            &options,
+        )?;
+        let policy_path = metadata
+            .workspace_root
+            .join(self.policy_file.as_deref().unwrap_or("deny.toml"));
+        let contents = fs::read_to_string(&policy_path).map_err(|e| {
+            anyhow!(
+                "Cannot read {}: {e}. Create a policy such as:\n\n{EXAMPLE_POLICY}",
+                policy_path.display()
+            )
+        })?;
+        let policy = Policy::parse(&contents)?;
+        let violations = policy.check(&metadata, &checks);
+
+        let mut result = format!(
+            "=== cargo policy ===\n📁 Working directory: {}\n📜 Policy: {}\n📦 {} packages, \
+             checking {}\n",
+            project_path.display(),
+            policy_path.display(),
+            metadata.packages.len(),
+            checks.join(", ")
+        );
+        if checks.contains(&"licenses") && policy.licenses.is_none() {
+            result.push_str("ℹ️  No [licenses] table in the policy, so licenses are not checked\n");
+        }
+
+        let sections = [
+            ("licenses", "⚖️  Licenses"),
+            ("sources", "🌐 Sources"),
+            ("bans", "🚫 Bans"),
+            ("duplicates", "👯 Duplicates"),
+        ];
+        for (check, heading) in sections {
+            let found = violations
+                .iter()
+                .filter(|violation| violation.check == check)
+                .collect::<Vec<_>>();
+            if found.is_empty() {
+                continue;
+            }
+            let _ = writeln!(result, "\n{heading}:");
+            for violation in found {
+                render_violation(&mut result, violation);
+            }
+        }
+
+        let errors = violations
+            .iter()
+            .filter(|violation| violation.level == "error")
+            .count();
+        let warnings = violations.len() - errors;
+        result.push('\n');
+        if violations.is_empty() {
+            result.push_str("✅ No policy violations\n");
+        } else {
+            let _ = writeln!(
+                result,
+                "{} {errors} error{}, {warnings} warning{}",
+                if errors > 0 { "❌" } else { "⚠️ " },
+                if errors == 1 { "" } else { "s" },
+                if warnings == 1 { "" } else { "s" }
+            );
+            result.push_str("\n🧾 Structured violations (JSON):\n");
+            result.push_str(&serde_json::to_string(&violations).unwrap_or_default());
+            result.push('\n');
+        }
+
+        Ok(fit_output(&result, limits, "a larger max_output_bytes"))
+    }
+}
+
+fn render_violation(result: &mut String, violation: &Violation) {
+    let icon = if violation.level == "error" {
+        "❌"
+    } else {
+        "⚠️ "
+    };
+    let _ = writeln!(
+        result,
+        "   {icon} {}: {}",
+        violation.package, violation.message
+    );
+    for chain in &violation.chains {
+        let _ = writeln!(result, "      🔗 {}", chain.join(" → "));
+    }
+}
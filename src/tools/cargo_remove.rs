@@ -1,6 +1,7 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
-use anyhow::{anyhow, Result};
+use crate::tools::cargo_utils::{RunOptions, create_cargo_command, execute_with_lockfile_diff};
+use anyhow::{Result, anyhow};
 use mcplease::{
     traits::{Tool, WithExamples},
     types::Example,
@@ -12,6 +13,12 @@ use std::collections::HashMap;
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_remove")]
 pub struct CargoRemove {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
     /// List of dependencies to remove
     pub dependencies: Vec<String>,
 
@@ -25,6 +32,23 @@ pub struct CargoRemove {
     #[arg(long)]
     pub dev: Option<bool>,
 
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -42,9 +66,13 @@ impl WithExamples for CargoRemove {
             Example {
                 description: "Remove a dependency",
                 item: Self {
+                    path: None,
                     dependencies: vec!["unused-crate".into()],
                     package: None,
                     dev: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -52,9 +80,13 @@ impl WithExamples for CargoRemove {
             Example {
                 description: "Remove multiple dependencies",
                 item: Self {
+                    path: None,
                     dependencies: vec!["old-lib".into(), "deprecated-crate".into()],
                     package: None,
                     dev: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -62,9 +94,13 @@ impl WithExamples for CargoRemove {
             Example {
                 description: "Remove a dev dependency",
                 item: Self {
+                    path: None,
                     dependencies: vec!["old-test-util".into()],
                     package: None,
                     dev: Some(true),
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -75,19 +111,28 @@ impl WithExamples for CargoRemove {
 
 impl Tool<CargoTools> for CargoRemove {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_remove", &self);
         if self.dependencies.is_empty() {
             return Err(anyhow!("No dependencies specified"));
         }
 
-        let project_path = state.ensure_rust_project()?;
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let mut args = vec!["remove"];
-        
+
         if let Some(ref package) = self.package {
             args.extend_from_slice(&["--package", package]);
         }
@@ -102,6 +147,18 @@ impl Tool<CargoTools> for CargoRemove {
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo remove", None)
+        execute_with_lockfile_diff(
+            state,
+            cmd,
+            &project_path,
+            "cargo remove",
+            RunOptions {
+                timeout_secs,
+                limits,
+                invocation,
+                toolchain,
+                ..RunOptions::default()
+            },
+        )
     }
 }
@@ -1,27 +1,28 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
 use crate::tools::cargo_utils::{
-    create_cargo_command, execute_cargo_command, wrap_command_for_pty,
+    OutputFormat, RunOptions, create_cargo_command, execute_or_spawn, wrap_command_for_pty,
 };
+use crate::tools::compile_options::{CompileOptions, Features};
 use anyhow::Result;
 use mcplease::traits::{Tool, WithExamples};
 use mcplease::types::Example;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
 
 /// Run a binary or example from the current package
 #[derive(Default, Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_run")]
 pub struct CargoRun {
-    /// Path to the project directory (containing Cargo.toml)
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
     pub path: Option<String>,
 
-    /// Optional package name to run from (for workspaces)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[arg(long)]
-    pub package: Option<String>,
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
 
     /// Optional binary name to run (if package has multiple binaries)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -33,40 +34,38 @@ pub struct CargoRun {
     #[arg(long)]
     pub example: Option<String>,
 
-    /// Run in release mode (optimized)
+    /// Arguments to pass to the binary after `--`
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub release: Option<bool>,
+    pub args: Option<Vec<String>>,
 
-    /// Space-separated list of features to activate
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub features: Option<String>,
+    pub background: Option<bool>,
 
-    /// Activate all available features
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub all_features: Option<bool>,
+    pub toolchain: Option<String>,
 
-    /// Do not activate the `default` feature
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (default: CARGO_MCP_DEFAULT_TIMEOUT if set, otherwise 120, or none in the background)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub no_default_features: Option<bool>,
+    pub timeout: Option<u64>,
 
-    /// Arguments to pass to the binary after `--`
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub args: Option<Vec<String>>,
+    pub max_output_bytes: Option<usize>,
 
-    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub toolchain: Option<String>,
-
-    /// Optional timeout in seconds (default: 120)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[arg(long)]
-    pub timeout: Option<u64>,
+    pub max_lines: Option<usize>,
 
     /// Optional environment variables to set for the cargo command
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -113,8 +112,11 @@ impl WithExamples for CargoRun {
             Example {
                 description: "Run in release mode with specific features",
                 item: Self {
-                    release: Some(true),
-                    features: Some("feature1 feature2".into()),
+                    compile: CompileOptions {
+                        release: Some(true),
+                        features: Some(Features::Joined("feature1 feature2".into())),
+                        ..CompileOptions::default()
+                    },
                     timeout: Some(120),
                     ..Self::default()
                 },
@@ -122,7 +124,10 @@ impl WithExamples for CargoRun {
             Example {
                 description: "Run a binary from a specific workspace package",
                 item: Self {
-                    package: Some("my-workspace-crate".into()),
+                    compile: CompileOptions {
+                        package: Some("my-workspace-crate".into()),
+                        ..CompileOptions::default()
+                    },
                     bin: Some("worker".into()),
                     args: Some(vec!["--config".into(), "prod.toml".into()]),
                     timeout: Some(120),
@@ -132,9 +137,12 @@ impl WithExamples for CargoRun {
             Example {
                 description: "Run with custom timeout (60 seconds)",
                 item: Self {
+                    compile: CompileOptions {
+                        release: Some(true),
+                        features: Some(Features::List(vec!["no-battery".into()])),
+                        ..CompileOptions::default()
+                    },
                     bin: Some("gps-tracker-tr003-v2".into()),
-                    release: Some(true),
-                    features: Some("no-battery".into()),
                     timeout: Some(60),
                     ..Self::default()
                 },
@@ -153,25 +161,29 @@ impl WithExamples for CargoRun {
 
 impl Tool<CargoTools> for CargoRun {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = if let Some(ref path) = self.path {
-            PathBuf::from(path)
-        } else {
-            state.ensure_rust_project()?
-        };
+        let invocation = Invocation::new("cargo_run", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
         let toolchain = self
             .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
-        // Use provided timeout or default to 120 seconds (2 minutes)
-        let timeout_secs = self.timeout.or(Some(120));
+        let background = self.background.unwrap_or(false);
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        // Use timeout from args, session default, or 120 seconds (2 minutes) in the foreground
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None))
+            .or((!background).then_some(120));
 
         let mut args = vec!["run"];
 
-        if let Some(ref package) = self.package {
-            args.extend_from_slice(&["--package", package]);
-        }
+        let compile_args = self.compile.args();
+        args.extend(compile_args.iter().map(String::as_str));
 
         if let Some(ref bin) = self.bin {
             args.extend_from_slice(&["--bin", bin]);
@@ -181,22 +193,6 @@ impl Tool<CargoTools> for CargoRun {
             args.extend_from_slice(&["--example", example]);
         }
 
-        if self.release.unwrap_or(false) {
-            args.push("--release");
-        }
-
-        if let Some(ref features) = self.features {
-            args.extend_from_slice(&["--features", features]);
-        }
-
-        if self.all_features.unwrap_or(false) {
-            args.push("--all-features");
-        }
-
-        if self.no_default_features.unwrap_or(false) {
-            args.push("--no-default-features");
-        }
-
         if let Some(ref raw_args) = self.raw_args {
             args.extend(raw_args.split_whitespace().map(|s| s as &str));
         }
@@ -215,6 +211,19 @@ impl Tool<CargoTools> for CargoRun {
 
         wrap_command_for_pty(&mut cmd, &project_path);
 
-        execute_cargo_command(cmd, &project_path, "cargo run", timeout_secs)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo run",
+            RunOptions {
+                background,
+                timeout_secs,
+                format: OutputFormat::Plain,
+                limits,
+                invocation,
+                toolchain,
+            },
+        )
     }
 }
@@ -1,5 +1,8 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{OutputFormat, RunOptions, create_cargo_command, execute_or_spawn};
+use crate::tools::compile_options::{CompileOptions, TargetSelection};
+use crate::tools::diagnostics::MESSAGE_FORMAT_JSON;
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -12,10 +15,19 @@ use std::collections::HashMap;
 #[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_test")]
 pub struct CargoTest {
-    /// Optional package name to test (for workspaces)
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
-    pub package: Option<String>,
+    pub path: Option<String>,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub compile: CompileOptions,
+
+    #[serde(flatten)]
+    #[command(flatten)]
+    pub targets: TargetSelection,
 
     /// Optional specific test name to run
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -27,6 +39,34 @@ pub struct CargoTest {
     #[arg(long)]
     pub no_capture: Option<bool>,
 
+    /// Report how long each test took and list the slowest ones (needs a nightly toolchain)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub report_time: Option<bool>,
+
+    /// Run in the background and return a job ID immediately instead of waiting for cargo
+    /// to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub background: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -48,7 +88,10 @@ impl WithExamples for CargoTest {
             Example {
                 description: "Run tests for a specific package",
                 item: Self {
-                    package: Some("my-lib".into()),
+                    compile: CompileOptions {
+                        package: Some("my-lib".into()),
+                        ..CompileOptions::default()
+                    },
                     ..Self::default()
                 },
             },
@@ -66,6 +109,21 @@ impl WithExamples for CargoTest {
                     ..Self::default()
                 },
             },
+            Example {
+                description: "Find the slowest tests on nightly",
+                item: Self {
+                    report_time: Some(true),
+                    toolchain: Some("nightly".into()),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Run the test suite as a background job",
+                item: Self {
+                    background: Some(true),
+                    ..Self::default()
+                },
+            },
             Example {
                 description: "Run tests with custom environment",
                 item: Self {
@@ -85,29 +143,60 @@ impl WithExamples for CargoTest {
 
 impl Tool<CargoTools> for CargoTest {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_test", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
         let toolchain = self
             .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
-        let mut args = vec!["test"];
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
 
-        if let Some(ref package) = self.package {
-            args.extend_from_slice(&["--package", package]);
-        }
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let mut args = vec!["test", MESSAGE_FORMAT_JSON];
+
+        let compile_args = self.compile.args();
+        let target_args = self.targets.args();
+        args.extend(compile_args.iter().map(String::as_str));
+        args.extend(target_args.iter().map(String::as_str));
 
         if let Some(ref test_name) = self.test_name {
             args.push(test_name);
         }
 
-        // Add --nocapture if requested
+        // Arguments for the test harness go after "--"
+        let mut harness_args = Vec::new();
         if self.no_capture.unwrap_or(false) {
-            args.extend_from_slice(&["--", "--nocapture"]);
+            harness_args.push("--nocapture");
+        }
+        if self.report_time.unwrap_or(false) {
+            harness_args.extend_from_slice(&["-Z", "unstable-options", "--report-time"]);
+        }
+        if !harness_args.is_empty() {
+            args.push("--");
+            args.extend(harness_args);
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo test", None)
+        execute_or_spawn(
+            state,
+            cmd,
+            &project_path,
+            "cargo test",
+            RunOptions {
+                background: self.background.unwrap_or(false),
+                timeout_secs,
+                format: OutputFormat::TestResults,
+                limits,
+                invocation,
+                toolchain,
+            },
+        )
     }
 }
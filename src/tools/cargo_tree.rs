@@ -0,0 +1,285 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, execute_or_spawn, fit_run_output, push_section,
+    push_structured, run_in_foreground,
+};
+use crate::tools::compile_options::Features;
+use crate::tools::dependency_tree::parse_tree;
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Show the dependency tree, e.g. to find out why a crate is in the build or which crates
+/// come in several versions
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_tree")]
+pub struct CargoTree {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Package to use as the root of the tree (for workspaces)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Show the tree for every workspace member
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub workspace: Option<bool>,
+
+    /// Invert the tree for this package, showing everything that depends on it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub invert: Option<String>,
+
+    /// Only show dependencies that come in more than one version, and what pulls each in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub duplicates: Option<bool>,
+
+    /// Kinds of edges to show: "all", "normal", "build", "dev", "features", "no-normal",
+    /// "no-build", "no-dev" or "no-proc-macro". "features" shows which features enable each
+    /// dependency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub edges: Option<Vec<String>>,
+
+    /// Maximum depth of the tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub depth: Option<u32>,
+
+    /// Only include dependencies for this target triple, or "all" for every platform
+    /// (defaults to the host)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub target: Option<String>,
+
+    /// Packages to leave out of the tree, along with their dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub prune: Option<Vec<String>>,
+
+    /// Repeat shared dependencies everywhere they occur instead of marking repeats with (*)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_dedupe: Option<bool>,
+
+    /// Features to activate, as a list or a space- or comma-separated string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub features: Option<Features>,
+
+    /// Activate all features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_features: Option<bool>,
+
+    /// Do not activate the default feature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_default_features: Option<bool>,
+
+    /// Return the tree as nested JSON nodes instead of text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub structured: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for CargoTree {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Show the direct dependencies of the current project",
+                item: Self {
+                    depth: Some(1),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Find out why syn is in the build",
+                item: Self {
+                    invert: Some("syn".into()),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "List crates that come in more than one version",
+                item: Self {
+                    duplicates: Some(true),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Show which features pull in serde's dependencies, as JSON",
+                item: Self {
+                    package: Some("serde".into()),
+                    edges: Some(vec!["features".into()]),
+                    structured: Some(true),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoTree {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_tree", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let structured = self.structured.unwrap_or(false);
+        let mut args = vec!["tree"];
+
+        if let Some(ref package) = self.package {
+            args.extend_from_slice(&["--package", package]);
+        }
+
+        if self.workspace.unwrap_or(false) {
+            args.push("--workspace");
+        }
+
+        if let Some(ref invert) = self.invert {
+            args.extend_from_slice(&["--invert", invert]);
+        }
+
+        if self.duplicates.unwrap_or(false) {
+            args.push("--duplicates");
+        }
+
+        let edges_str;
+        if let Some(ref edges) = self.edges
+            && !edges.is_empty()
+        {
+            edges_str = edges.join(",");
+            args.extend_from_slice(&["--edges", &edges_str]);
+        }
+
+        let depth_str;
+        if let Some(depth) = self.depth {
+            depth_str = depth.to_string();
+            args.extend_from_slice(&["--depth", &depth_str]);
+        }
+
+        if let Some(ref target) = self.target {
+            args.extend_from_slice(&["--target", target]);
+        }
+
+        for prune in self.prune.iter().flatten() {
+            args.extend_from_slice(&["--prune", prune]);
+        }
+
+        if self.no_dedupe.unwrap_or(false) {
+            args.push("--no-dedupe");
+        }
+
+        let features_str;
+        let features = self.features.as_ref().map(Features::names);
+        if let Some(features) = features
+            && !features.is_empty()
+        {
+            features_str = features.join(",");
+            args.extend_from_slice(&["--features", &features_str]);
+        }
+
+        if self.all_features.unwrap_or(false) {
+            args.push("--all-features");
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            args.push("--no-default-features");
+        }
+
+        if structured {
+            args.extend_from_slice(&["--prefix", "depth"]);
+        }
+
+        let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        if !structured {
+            return execute_or_spawn(state, cmd, &project_path, "cargo tree", options);
+        }
+
+        let (output, full_output) = run_in_foreground(state, cmd, &project_path, &options)?;
+        if !output.succeeded() {
+            let result = OutputFormat::Plain.render(&output, "cargo tree", &project_path);
+            let result = fit_run_output(&result, limits, &full_output, &output);
+            if output.timed_out.is_some() || output.cancelled {
+                bail!(result);
+            }
+            return Ok(result);
+        }
+
+        let roots = parse_tree(&output.stdout);
+        let mut result = output.header("cargo tree", &project_path);
+        push_section(&mut result, "📤 STDERR:", &output.stderr);
+        if roots.is_empty() {
+            result.push_str("ℹ️  No dependencies to show\n");
+        } else {
+            push_structured(
+                &mut result,
+                "",
+                "🧾 Structured tree (JSON):",
+                &roots,
+                limits,
+            )?;
+        }
+
+        Ok(fit_run_output(&result, limits, &full_output, &output))
+    }
+}
@@ -1,5 +1,8 @@
+use crate::runs::Invocation;
 use crate::state::CargoTools;
-use crate::tools::cargo_utils::{create_cargo_command, execute_cargo_command};
+use crate::tools::cargo_utils::{
+    RunOptions, create_cargo_command, execute_or_spawn, execute_with_lockfile_diff,
+};
 use anyhow::Result;
 use mcplease::{
     traits::{Tool, WithExamples},
@@ -12,6 +15,12 @@ use std::collections::HashMap;
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
 #[serde(rename = "cargo_update")]
 pub struct CargoUpdate {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
     /// Optional package name (for workspaces)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -27,6 +36,23 @@ pub struct CargoUpdate {
     #[arg(long)]
     pub dry_run: Option<bool>,
 
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
     /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
     #[serde(skip_serializing_if = "Option::is_none")]
     #[arg(long)]
@@ -44,9 +70,13 @@ impl WithExamples for CargoUpdate {
             Example {
                 description: "Update all dependencies",
                 item: Self {
+                    path: None,
                     package: None,
                     dependencies: None,
                     dry_run: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -54,9 +84,13 @@ impl WithExamples for CargoUpdate {
             Example {
                 description: "Dry run to see what would be updated",
                 item: Self {
+                    path: None,
                     package: None,
                     dependencies: None,
                     dry_run: Some(true),
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -64,9 +98,13 @@ impl WithExamples for CargoUpdate {
             Example {
                 description: "Update specific dependencies",
                 item: Self {
+                    path: None,
                     package: None,
                     dependencies: Some(vec!["serde".into(), "tokio".into()]),
                     dry_run: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -74,9 +112,13 @@ impl WithExamples for CargoUpdate {
             Example {
                 description: "Update dependencies for a specific package",
                 item: Self {
+                    path: None,
                     package: Some("my-lib".into()),
                     dependencies: None,
                     dry_run: None,
+                    timeout: None,
+                    max_output_bytes: None,
+                    max_lines: None,
                     toolchain: None,
                     cargo_env: None,
                 },
@@ -87,15 +129,24 @@ impl WithExamples for CargoUpdate {
 
 impl Tool<CargoTools> for CargoUpdate {
     fn execute(self, state: &mut CargoTools) -> Result<String> {
-        let project_path = state.ensure_rust_project()?;
+        let invocation = Invocation::new("cargo_update", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
 
         // Use toolchain from args, session default, or none
-        let toolchain = self.toolchain
+        let toolchain = self
+            .toolchain
             .or_else(|| state.get_default_toolchain().unwrap_or(None));
 
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
 
         let mut args = vec!["update"];
-        
+
         if let Some(ref package) = self.package {
             args.extend_from_slice(&["--package", package]);
         }
@@ -112,6 +163,18 @@ impl Tool<CargoTools> for CargoUpdate {
         }
 
         let cmd = create_cargo_command(&args, toolchain.as_deref(), self.cargo_env.as_ref());
-        execute_cargo_command(cmd, &project_path, "cargo update", None)
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        if self.dry_run.unwrap_or(false) {
+            // Nothing is written, so there's no lockfile change to report
+            execute_or_spawn(state, cmd, &project_path, "cargo update", options)
+        } else {
+            execute_with_lockfile_diff(state, cmd, &project_path, "cargo update", options)
+        }
     }
 }
@@ -1,27 +1,59 @@
+use crate::progress::ProgressTracker;
+use crate::project::find_workspace_root;
+use crate::runs::{Invocation, RunHistory};
+use crate::server::CancellationToken;
+use crate::state::CargoTools;
+use crate::tools::capture::{Capture, OutputLine, display_lines, strip_timestamp};
+use crate::tools::diagnostics::{format_diagnostics_output, format_lint_output};
+use crate::tools::lockfile::Lockfile;
+use crate::tools::test_results::format_test_output;
 use anyhow::{Result, bail};
+use serde::Serialize;
 use std::{
     collections::HashMap,
-    io::Read,
-    path::PathBuf,
-    process::{Command, Stdio},
+    io,
+    path::Path,
+    process::{Child, Command, ExitStatus, Stdio},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+/// How long a process group gets to exit after SIGTERM before it is sent SIGKILL
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Response size used when neither the call nor CARGO_MCP_MAX_OUTPUT_BYTES sets one, roughly
+/// ten thousand tokens
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 40_000;
+
+/// Progress lines cargo prints once per crate, which are collapsed into a count when a response
+/// has to be shortened
+const PROGRESS_VERBS: [&str; 2] = ["Compiling", "Checking"];
+
 /// Helper to create a cargo command with optional toolchain and environment variables
 pub fn create_cargo_command(
     cargo_args: &[&str],
     toolchain: Option<&str>,
     env_vars: Option<&HashMap<String, String>>,
+) -> Command {
+    create_toolchain_command("cargo", cargo_args, toolchain, env_vars)
+}
+
+/// Create a command for a toolchain binary such as cargo or rustfmt, run through rustup when
+/// a toolchain is given
+pub fn create_toolchain_command(
+    program: &str,
+    args: &[&str],
+    toolchain: Option<&str>,
+    env_vars: Option<&HashMap<String, String>>,
 ) -> Command {
     let mut cmd = if let Some(toolchain) = toolchain {
         let mut cmd = Command::new("rustup");
-        cmd.args(["run", toolchain, "cargo"]);
-        cmd.args(cargo_args);
+        cmd.args(["run", toolchain, program]);
+        cmd.args(args);
         cmd
     } else {
-        let mut cmd = Command::new("cargo");
-        cmd.args(cargo_args);
+        let mut cmd = Command::new(program);
+        cmd.args(args);
         cmd
     };
 
@@ -37,7 +69,7 @@ pub fn create_cargo_command(
 
 /// Wrap a command with `script` to provide a PTY, required for espflash monitor mode
 /// which uses crossterm for terminal input handling
-pub fn wrap_command_for_pty(cmd: &mut Command, project_path: &PathBuf) {
+pub fn wrap_command_for_pty(cmd: &mut Command, project_path: &Path) {
     let program = cmd.get_program().to_string_lossy().to_string();
     let args = cmd
         .get_args()
@@ -63,112 +95,639 @@ pub fn wrap_command_for_pty(cmd: &mut Command, project_path: &PathBuf) {
 
         if cargo_config_exists {
             *cmd = Command::new("script");
-            cmd.args(&["-q", "-c", &combined, "/dev/null"]);
+            cmd.args(["-q", "-c", &combined, "/dev/null"]);
+        }
+    }
+}
+
+/// Output captured from a finished cargo command
+#[derive(Debug)]
+pub struct CargoOutput {
+    /// The command line that was run, formatted for display
+    pub command: String,
+    /// Exit status of the process
+    pub status: ExitStatus,
+    /// How long the process ran
+    pub duration: Duration,
+    /// Everything the process wrote to stdout
+    pub stdout: String,
+    /// Everything the process wrote to stderr
+    pub stderr: String,
+    /// Both streams interleaved in the order the lines arrived
+    pub lines: Vec<OutputLine>,
+    /// The timeout in seconds, if the command exceeded it and was killed
+    pub timed_out: Option<u64>,
+    /// Whether the command was killed because its request or job was cancelled
+    pub cancelled: bool,
+}
+
+impl CargoOutput {
+    /// Whether the command ran to completion and exited successfully
+    pub fn succeeded(&self) -> bool {
+        self.status.success() && self.timed_out.is_none() && !self.cancelled
+    }
+
+    /// Render the common response header: command name, working directory, command line and
+    /// exit status
+    pub fn header(&self, command_name: &str, project_path: &Path) -> String {
+        let mut result = format!("=== {command_name} ===\n");
+        result.push_str(&format!(
+            "📁 Working directory: {}\n",
+            project_path.display()
+        ));
+        result.push_str(&format!("🔧 Command: {}\n\n", self.command));
+
+        if self.cancelled {
+            result.push_str(
+                "🛑 Command cancelled and its process tree was killed. Partial output follows.\n\n",
+            );
+        } else if let Some(timeout_secs) = self.timed_out {
+            result.push_str(&format!(
+                "⏱️  Command timed out after {timeout_secs} seconds and its process tree was \
+                 killed. Partial output follows.\n\n"
+            ));
+        } else if self.status.success() {
+            result.push_str("✅ Command completed successfully\n\n");
+        } else {
+            result.push_str(&format!(
+                "❌ Command failed with exit code: {}\n\n",
+                self.status.code().unwrap_or(-1)
+            ));
         }
+
+        result
+    }
+}
+
+/// Append a labelled block of output to a response, skipping it if empty
+pub fn push_section(result: &mut String, label: &str, content: &str) {
+    if content.is_empty() {
+        return;
+    }
+
+    result.push_str(label);
+    result.push('\n');
+    result.push_str(content);
+    if !content.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push('\n');
+}
+
+/// Append a tool's text summary and the same results as JSON, for tools whose main output is
+/// the JSON. The JSON is pretty-printed, so truncation keeps whole lines of it and the start of
+/// the response is a valid prefix of the document. When both don't fit in the limits, the
+/// summary is left out so that the JSON gets the budget.
+pub fn push_structured(
+    result: &mut String,
+    summary: &str,
+    label: &str,
+    value: &(impl Serialize + ?Sized),
+    limits: OutputLimits,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    let lines = result
+        .lines()
+        .chain(summary.lines())
+        .chain(json.lines())
+        .collect::<Vec<_>>();
+    if limits.fits(&lines) {
+        result.push_str(summary);
+    } else if !summary.is_empty() {
+        result.push_str(
+            "📋 The text summary is left out to fit the response limit; the JSON below has the \
+             same content\n\n",
+        );
     }
+    result.push_str(label);
+    result.push('\n');
+    result.push_str(&json);
+    result.push('\n');
+    Ok(())
 }
 
-/// Execute a cargo command and format the output for MCP response
-pub fn execute_cargo_command(
+/// Start a command in its own process group, detached from the server's stdin (which carries
+/// the MCP protocol), so that it and everything it spawns can be killed together
+pub fn isolate_process_group(cmd: &mut Command) {
+    cmd.stdin(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+}
+
+/// Kill a child started with [`isolate_process_group`] together with every process in its
+/// group: cargo, rustc, test binaries and any `script` wrapper
+pub fn kill_process_tree(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let process_group = -(child.id() as libc::pid_t);
+
+        // SAFETY: kill(2) has no memory-safety requirements; a negative pid targets the group
+        unsafe { libc::kill(process_group, libc::SIGTERM) };
+
+        let deadline = Instant::now() + KILL_GRACE_PERIOD;
+        while child.try_wait()?.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        // Anything that ignored SIGTERM, including grandchildren that outlived cargo
+        // SAFETY: as above
+        unsafe { libc::kill(process_group, libc::SIGKILL) };
+    }
+
+    #[cfg(not(unix))]
+    let _ = child.kill();
+
+    child.wait().map(|_| ())
+}
+
+/// Run a cargo command to completion and capture its output, reporting progress as it goes if
+/// the client asked for it. If the timeout is exceeded or the request is cancelled, the process
+/// tree is killed and whatever output was collected is returned with `timed_out` or `cancelled`
+/// set.
+pub fn run_cargo_command(
     mut cmd: Command,
-    project_path: &PathBuf,
-    command_name: &str,
+    project_path: &Path,
     timeout_secs: Option<u64>,
-) -> Result<String> {
+    progress: Option<ProgressTracker>,
+    cancellation: Option<CancellationToken>,
+) -> Result<CargoOutput> {
     cmd.current_dir(project_path);
+    isolate_process_group(&mut cmd);
 
     // Capture output for display
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
+    if progress.is_some() {
+        // Cargo only draws its progress bar on a terminal unless told otherwise
+        cmd.env("CARGO_TERM_PROGRESS_WHEN", "always");
+        cmd.env("CARGO_TERM_PROGRESS_WIDTH", "200");
+    }
+
     let mut child = cmd.spawn()?;
+    let capture = Capture::start(&mut child, progress);
+
+    let deadline = timeout_secs.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let mut timed_out = None;
+    let mut cancelled = false;
 
-    let timeout_duration = timeout_secs.map(Duration::from_secs);
-
-    let output = match timeout_duration {
-        Some(timeout) => {
-            // Wait for child with timeout
-            let start = std::time::Instant::now();
-            loop {
-                match child.try_wait() {
-                    Ok(Some(status)) => break Ok(status),
-                    Ok(None) => {
-                        if start.elapsed() > timeout {
-                            // Kill the child and return timeout error
-                            let _ = child.kill();
-                            let _ = child.wait();
-                            bail!(
-                                "❌ Command timed out after {} seconds\n",
-                                timeout_secs.unwrap()
-                            );
-                        }
-                        thread::sleep(Duration::from_millis(100));
-                    }
-                    Err(e) => break Err(e),
-                }
+    let status = if deadline.is_none() && cancellation.is_none() {
+        child.wait()?
+    } else {
+        loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
             }
-        }
-        None => child.wait(),
-    }?;
 
-    let stdout = child.stdout.take().unwrap();
-    let stderr = child.stderr.take().unwrap();
+            if cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                kill_process_tree(&mut child)?;
+                cancelled = true;
+                break child.wait()?;
+            }
 
-    // Read output from pipes
-    let mut stdout_reader = std::io::BufReader::new(stdout);
-    let mut stdout_bytes = Vec::new();
-    stdout_reader.read_to_end(&mut stdout_bytes)?;
+            if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                kill_process_tree(&mut child)?;
+                timed_out = timeout_secs;
+                break child.wait()?;
+            }
 
-    let mut stderr_reader = std::io::BufReader::new(stderr);
-    let mut stderr_bytes = Vec::new();
-    stderr_reader.read_to_end(&mut stderr_bytes)?;
+            thread::sleep(Duration::from_millis(50));
+        }
+    };
 
-    let stdout_str = String::from_utf8_lossy(&stdout_bytes);
-    let stderr_str = String::from_utf8_lossy(&stderr_bytes);
+    let mut output = capture.finish(format_command(&cmd), status, timed_out);
+    output.cancelled = cancelled;
+    Ok(output)
+}
 
-    let mut result = format!("=== {command_name} ===\n");
-    result.push_str(&format!(
-        "📁 Working directory: {}\n",
-        project_path.display()
-    ));
-    result.push_str(&format!("🔧 Command: {}\n\n", format_command(&cmd)));
+/// How a finished command's output is presented in the MCP response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Raw stdout and stderr
+    #[default]
+    Plain,
+    /// Compiler diagnostics parsed from `--message-format=json`
+    Diagnostics,
+    /// Compiler diagnostics, with a count of each lint that fired
+    Lints,
+    /// Per-test results parsed from libtest output
+    TestResults,
+}
 
-    if output.success() {
-        result.push_str("✅ Command completed successfully\n\n");
+impl OutputFormat {
+    /// Format a finished command's output for MCP response
+    pub fn render(self, output: &CargoOutput, command_name: &str, project_path: &Path) -> String {
+        match self {
+            Self::Plain => format_plain_output(output, command_name, project_path),
+            Self::Diagnostics => format_diagnostics_output(output, command_name, project_path),
+            Self::Lints => format_lint_output(output, command_name, project_path),
+            Self::TestResults => format_test_output(output, command_name, project_path),
+        }
+    }
+}
+
+fn format_plain_output(output: &CargoOutput, command_name: &str, project_path: &Path) -> String {
+    let mut result = output.header(command_name, project_path);
+
+    if !output.stdout.is_empty() && !output.stderr.is_empty() {
+        // Show both streams in the order a terminal would have, with arrival times
+        let interleaved = output
+            .lines
+            .iter()
+            .map(|line| line.timestamped() + "\n")
+            .collect::<String>();
+        push_section(
+            &mut result,
+            "📤 OUTPUT (stdout and stderr, interleaved):",
+            &interleaved,
+        );
     } else {
-        result.push_str(&format!(
-            "❌ Command failed with exit code: {}\n\n",
-            output.code().unwrap_or(-1)
-        ));
+        push_section(&mut result, "📤 STDOUT:", &output.stdout);
+        push_section(&mut result, "📤 STDERR:", &output.stderr);
+    }
+
+    if output.stdout.is_empty() && output.stderr.is_empty() {
+        result.push_str("ℹ️  No output produced\n");
     }
 
-    if !stdout_str.is_empty() {
-        result.push_str("📤 STDOUT:\n");
-        result.push_str(&stdout_str);
-        if !stdout_str.ends_with('\n') {
-            result.push('\n');
+    result
+}
+
+/// How large a response may get before it is summarized and truncated. `None` means no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputLimits {
+    pub max_bytes: Option<usize>,
+    pub max_lines: Option<usize>,
+}
+
+impl OutputLimits {
+    fn fits<S: AsRef<str>>(&self, lines: &[S]) -> bool {
+        let bytes = lines
+            .iter()
+            .map(|line| line.as_ref().len() + 1)
+            .sum::<usize>();
+        self.max_bytes.is_none_or(|max| bytes <= max)
+            && self.max_lines.is_none_or(|max| lines.len() <= max)
+    }
+}
+
+/// Replace runs of "Compiling foo v1.2.3" lines with a single line counting them
+fn collapse_progress_lines(lines: Vec<String>, notes: &mut Vec<String>) -> Vec<String> {
+    let verb = |line: &str| {
+        let text = strip_timestamp(line).trim_start();
+        PROGRESS_VERBS.into_iter().find(|verb| {
+            text.strip_prefix(verb)
+                .is_some_and(|rest| rest.starts_with(' '))
+        })
+    };
+
+    let mut collapsed = Vec::new();
+    let mut removed = 0;
+    let mut index = 0;
+    while index < lines.len() {
+        let Some(run_verb) = verb(&lines[index]) else {
+            collapsed.push(lines[index].clone());
+            index += 1;
+            continue;
+        };
+
+        let run_length = lines[index..]
+            .iter()
+            .take_while(|line| verb(line) == Some(run_verb))
+            .count();
+        if run_length < 3 {
+            collapsed.extend(lines[index..index + run_length].iter().cloned());
+        } else {
+            let crate_name = |line: &str| {
+                let text = strip_timestamp(line).trim_start();
+                text[run_verb.len()..].trim().to_string()
+            };
+            let first = &lines[index];
+            let prefix = &first[..first.len() - strip_timestamp(first).trim_start().len()];
+            collapsed.push(format!(
+                "{prefix}{run_verb} {run_length} crates ({} … {})",
+                crate_name(first),
+                crate_name(&lines[index + run_length - 1])
+            ));
+            removed += run_length - 1;
         }
-        result.push('\n');
+        index += run_length;
+    }
+
+    if removed > 0 {
+        notes.push(format!("collapsed {removed} per-crate progress lines"));
     }
+    collapsed
+}
+
+/// Drop warnings identical to one already shown. A warning runs from its `warning:` line to the
+/// next blank line.
+fn dedupe_warnings(lines: Vec<String>, notes: &mut Vec<String>) -> Vec<String> {
+    let starts_warning = |line: &str| {
+        let text = strip_timestamp(line);
+        text.starts_with("warning:") || text.starts_with("warning[")
+    };
 
-    if !stderr_str.is_empty() {
-        result.push_str("📤 STDERR:\n");
-        result.push_str(&stderr_str);
-        if !stderr_str.ends_with('\n') {
-            result.push('\n');
+    let mut seen = std::collections::HashSet::new();
+    let mut deduped = Vec::new();
+    let mut duplicates = 0;
+    let mut index = 0;
+    while index < lines.len() {
+        if !starts_warning(&lines[index]) {
+            deduped.push(lines[index].clone());
+            index += 1;
+            continue;
         }
-        result.push('\n');
+
+        let end = lines[index..]
+            .iter()
+            .position(|line| strip_timestamp(line).trim().is_empty())
+            .map_or(lines.len(), |offset| index + offset + 1);
+        let block = &lines[index..end];
+        let key = block
+            .iter()
+            .map(|line| strip_timestamp(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if seen.insert(key) {
+            deduped.extend(block.iter().cloned());
+        } else {
+            duplicates += 1;
+        }
+        index = end;
     }
 
-    if stdout_str.is_empty() && stderr_str.is_empty() {
-        result.push_str("ℹ️  No output produced\n");
+    if duplicates > 0 {
+        notes.push(format!("dropped {duplicates} duplicate warnings"));
+    }
+    deduped
+}
+
+/// Cut lines too long to fit in half the byte limit, such as single-line JSON, to a quarter of
+/// it at a char boundary, so that keeping the beginning and end doesn't drop them whole
+fn cut_long_lines(
+    lines: Vec<String>,
+    limits: OutputLimits,
+    notes: &mut Vec<String>,
+) -> Vec<String> {
+    let Some(max_bytes) = limits.max_bytes else {
+        return lines;
+    };
+    let max_line = max_bytes / 4;
+
+    let mut cut = 0;
+    let lines = lines
+        .into_iter()
+        .map(|line| {
+            if line.len() <= max_bytes / 2 {
+                return line;
+            }
+            let mut end = max_line;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            cut += 1;
+            format!(
+                "{} … ✂️  {} more bytes on this line",
+                &line[..end],
+                line.len() - end
+            )
+        })
+        .collect();
+
+    if cut > 0 {
+        notes.push(format!("cut {cut} long lines"));
+    }
+    lines
+}
+
+/// Keep as many lines from the beginning and end as fit in the limits, replacing the middle
+/// with a marker that says how to read the run's log, of `log_lines` lines if known
+fn keep_head_and_tail(
+    lines: Vec<String>,
+    limits: OutputLimits,
+    full_output: &str,
+    log_lines: Option<usize>,
+    notes: &mut Vec<String>,
+) -> Vec<String> {
+    let half_lines = limits.max_lines.map_or(usize::MAX, |max| max / 2);
+    let half_bytes = limits.max_bytes.map_or(usize::MAX, |max| max / 2);
+    let fitting = |lines: &mut dyn Iterator<Item = &String>| {
+        let mut bytes = 0;
+        lines
+            .take(half_lines)
+            .take_while(|line| {
+                bytes += line.len() + 1;
+                bytes <= half_bytes
+            })
+            .count()
+    };
+
+    let head = fitting(&mut lines.iter());
+    let tail = fitting(&mut lines[head..].iter().rev());
+    let elided = &lines[head..lines.len() - tail];
+    let elided_bytes = elided.iter().map(|line| line.len() + 1).sum::<usize>();
+    notes.push(format!(
+        "elided {} lines ({elided_bytes} bytes) from the middle",
+        elided.len()
+    ));
+
+    let mut kept = lines[..head].to_vec();
+    kept.push(match log_lines {
+        Some(log_lines) => format!(
+            "… ✂️  {} lines ({elided_bytes} bytes) elided here, use {full_output} to read the \
+             run's log ({log_lines} lines) …",
+            elided.len()
+        ),
+        None => format!(
+            "… ✂️  {} lines ({elided_bytes} bytes) elided here, use {full_output} to read them …",
+            elided.len()
+        ),
+    });
+    kept.extend(lines[lines.len() - tail..].iter().cloned());
+    kept
+}
+
+/// Fit a response into the output limits. Responses that already fit are returned unchanged.
+/// Otherwise repeated "Compiling" lines are collapsed and duplicate warnings dropped, and if the
+/// response is still too large overlong lines are cut and only its beginning and end are kept. `full_output` names the
+/// follow-up call that returns the complete log.
+pub fn fit_output(text: &str, limits: OutputLimits, full_output: &str) -> String {
+    fit_logged_output(text, limits, full_output, None)
+}
+
+/// Fit the response to a recorded run into the output limits like [`fit_output`], with the
+/// length of the run's log in the truncation marker. The response is rendered from the log, not
+/// a copy of it, so its lines can't be mapped back to log lines.
+pub fn fit_run_output(
+    text: &str,
+    limits: OutputLimits,
+    full_output: &str,
+    output: &CargoOutput,
+) -> String {
+    // The log as get_run_output numbers it: one entry per line of the recorded log file
+    let log_lines = display_lines(&output.lines, false)
+        .iter()
+        .map(|line| line.lines().count())
+        .sum();
+    fit_logged_output(text, limits, full_output, Some(log_lines))
+}
+
+/// Fit a response into the output limits like [`fit_output`], given the number of lines in the
+/// log that `full_output` reads
+pub fn fit_logged_output(
+    text: &str,
+    limits: OutputLimits,
+    full_output: &str,
+    log_lines: Option<usize>,
+) -> String {
+    let lines = text.lines().map(String::from).collect::<Vec<_>>();
+    if limits.fits(&lines) {
+        return text.to_string();
+    }
+
+    let mut notes = Vec::new();
+    let lines = collapse_progress_lines(lines, &mut notes);
+    let lines = dedupe_warnings(lines, &mut notes);
+    let lines = if limits.fits(&lines) {
+        lines
+    } else {
+        cut_long_lines(lines, limits, &mut notes)
+    };
+    let lines = if limits.fits(&lines) {
+        lines
+    } else {
+        keep_head_and_tail(lines, limits, full_output, log_lines, &mut notes)
+    };
+
+    let mut result = lines.join("\n").trim_end().to_string();
+    result.push_str(&format!(
+        "\n\n✂️  Output shortened to fit the response limit: {}. Use {full_output} for the full \
+         log.\n",
+        notes.join(", ")
+    ));
+    result
+}
+
+/// How to run a cargo command, present its output and record it in the run history
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Start a background job and return its ID instead of waiting for cargo to finish
+    pub background: bool,
+    /// Kill the process tree after this many seconds
+    pub timeout_secs: Option<u64>,
+    pub format: OutputFormat,
+    pub limits: OutputLimits,
+    /// The tool call being served, for the run history
+    pub invocation: Invocation,
+    pub toolchain: Option<String>,
+}
+
+impl RunOptions {
+    /// Record a finished run, returning a follow-up call that reads its full log
+    pub fn record(&self, runs: &RunHistory, project_path: &Path, output: &CargoOutput) -> String {
+        match runs.record(
+            &self.invocation,
+            self.toolchain.as_deref(),
+            project_path,
+            output,
+        ) {
+            Ok(run_id) => format!("get_run_output with run_id {run_id}"),
+            Err(e) => {
+                log::warn!("failed to record run: {e}");
+                "a larger max_output_bytes".to_string()
+            }
+        }
     }
+}
 
+/// Execute a cargo command in the foreground, or start it as a background job and return the
+/// job ID immediately if `options.background` is set. Every run is recorded in the run history.
+pub fn execute_or_spawn(
+    state: &mut CargoTools,
+    cmd: Command,
+    project_path: &Path,
+    command_name: &str,
+    options: RunOptions,
+) -> Result<String> {
+    if options.background {
+        let runs = state.runs().clone();
+        let id = state
+            .jobs_mut()
+            .spawn(cmd, project_path, command_name, options, runs)?;
+        return Ok(format!(
+            "🚀 Started {command_name} as background job {id}\n\n\
+             Use job_status, job_output, job_wait or job_cancel with job_id {id} to follow it.\n"
+        ));
+    }
+
+    let (output, full_output) = run_in_foreground(state, cmd, project_path, &options)?;
+    let rendered = options.format.render(&output, command_name, project_path);
+    let result = fit_run_output(&rendered, options.limits, &full_output, &output);
+    if output.timed_out.is_some() || output.cancelled {
+        bail!(result);
+    }
     Ok(result)
 }
 
+/// Execute a command that may change Cargo.lock in the foreground, following cargo's output
+/// with the packages it added, removed, upgraded or downgraded in the lockfile
+pub fn execute_with_lockfile_diff(
+    state: &mut CargoTools,
+    cmd: Command,
+    project_path: &Path,
+    command_name: &str,
+    options: RunOptions,
+) -> Result<String> {
+    // A missing lockfile counts as empty, so a newly created one lists every package as added
+    let workspace_root = find_workspace_root(project_path);
+    let before = Lockfile::load(&workspace_root).unwrap_or_default();
+
+    let (output, full_output) = run_in_foreground(state, cmd, project_path, &options)?;
+    let mut rendered = options.format.render(&output, command_name, project_path);
+    match Lockfile::load(&workspace_root) {
+        Ok(after) => before.diff(&after).render(&mut rendered),
+        Err(e) => log::warn!("cannot compare Cargo.lock: {e}"),
+    }
+
+    let result = fit_run_output(&rendered, options.limits, &full_output, &output);
+    if output.timed_out.is_some() || output.cancelled {
+        bail!(result);
+    }
+    Ok(result)
+}
+
+/// Run a command to completion with the current request's progress reporting and
+/// cancellation, and record it in the run history. Returns the output along with the follow-up
+/// call that reads its full log, for tools that present cargo's output themselves.
+pub fn run_in_foreground(
+    state: &mut CargoTools,
+    cmd: Command,
+    project_path: &Path,
+    options: &RunOptions,
+) -> Result<(CargoOutput, String)> {
+    let progress = state.progress_token().cloned().map(ProgressTracker::new);
+    let cancellation = state.cancellation().cloned();
+    let output = run_cargo_command(
+        cmd,
+        project_path,
+        options.timeout_secs,
+        progress,
+        cancellation,
+    )?;
+    let full_output = options.record(state.runs(), project_path, &output);
+    Ok((output, full_output))
+}
+
 /// Format a command for display
-fn format_command(cmd: &Command) -> String {
+pub fn format_command(cmd: &Command) -> String {
     let program = cmd.get_program().to_string_lossy();
     let args = cmd
         .get_args()
@@ -0,0 +1,463 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{RunOptions, fit_output};
+use crate::tools::file_changes::{FileChange, push_changes};
+use crate::tools::metadata::Metadata;
+use crate::tools::workspace_deps::{
+    Declaration, Manifest, Promotion, declarations, promote, workspace_dependencies,
+};
+use anyhow::{Result, bail};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+    fs,
+};
+
+/// Manage `[workspace.dependencies]`: report version drift between members, promote a
+/// member's dependency to the workspace table so members inherit it with `workspace = true`,
+/// or consolidate every dependency that several members declare
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "cargo_workspace_deps")]
+pub struct CargoWorkspaceDeps {
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// "report" (default) lists version drift and dependencies that could be shared,
+    /// "promote" moves the named dependencies to the workspace table and "consolidate" moves
+    /// every dependency declared by at least min_members members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub action: Option<String>,
+
+    /// Dependencies to promote, or to limit consolidation to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub dependencies: Option<Vec<String>>,
+
+    /// Version requirement for the workspace entry when promoting a single dependency
+    /// (default: the workspace's existing entry, or the highest version a member asks for)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub version: Option<String>,
+
+    /// Number of members that must declare a dependency for consolidate to move it (default:
+    /// 2). Dependencies already in the workspace table are always moved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub min_members: Option<usize>,
+
+    /// Show the changes without writing them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub dry_run: Option<bool>,
+
+    /// Include a unified diff of the changed manifests (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub diff: Option<bool>,
+
+    /// Optional timeout in seconds, after which cargo and everything it started is killed
+    /// (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Maximum response size in bytes; larger output is summarized and truncated, keeping the
+    /// beginning and end (defaults to CARGO_MCP_MAX_OUTPUT_BYTES or 40000, 0 for no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_output_bytes: Option<usize>,
+
+    /// Maximum response size in lines (defaults to CARGO_MCP_MAX_LINES, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub max_lines: Option<usize>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+/// What the report found
+#[derive(Debug, Default, Serialize)]
+struct Report<'a> {
+    /// Dependencies that members ask for at different versions
+    drift: BTreeMap<&'a str, Vec<&'a Declaration>>,
+    /// Dependencies several members declare that the workspace table doesn't have
+    shared: BTreeMap<&'a str, Vec<&'a Declaration>>,
+    /// Declarations of dependencies the workspace table has, which could inherit it
+    inheritable: Vec<&'a Declaration>,
+}
+
+/// A dependency consolidate left alone
+#[derive(Debug, Serialize)]
+struct Skipped {
+    name: String,
+    reason: String,
+}
+
+impl WithExamples for CargoWorkspaceDeps {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Report version drift and dependencies members could share",
+                item: Self::default(),
+            },
+            Example {
+                description: "Move serde to [workspace.dependencies] at a given version",
+                item: Self {
+                    action: Some("promote".into()),
+                    dependencies: Some(vec!["serde".into()]),
+                    version: Some("1.0.200".into()),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Preview consolidating every dependency that two or more members \
+                              declare",
+                item: Self {
+                    action: Some("consolidate".into()),
+                    dry_run: Some(true),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for CargoWorkspaceDeps {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("cargo_workspace_deps", &self);
+        let project_path = state.resolve_project(self.path.as_deref())?;
+
+        // Use toolchain from args, session default, or none
+        let toolchain = self
+            .toolchain
+            .clone()
+            .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+        // Use timeout from args, session default, or none
+        let timeout_secs = self
+            .timeout
+            .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+        // Use response limits from args, session defaults, or the built-in budget
+        let limits = state.output_limits(self.max_output_bytes, self.max_lines);
+
+        let action = self.action.as_deref().unwrap_or("report");
+        if !matches!(action, "report" | "promote" | "consolidate") {
+            bail!("Unknown action {action:?}: use report, promote or consolidate");
+        }
+        let names = self.dependencies.clone().unwrap_or_default();
+        if action == "promote" && names.is_empty() {
+            bail!("Name the dependencies to promote");
+        }
+        if self.version.is_some() && (action != "promote" || names.len() != 1) {
+            bail!("version applies to promoting a single dependency");
+        }
+        let min_members = self.min_members.unwrap_or(2);
+
+        let options = RunOptions {
+            timeout_secs,
+            limits,
+            invocation,
+            toolchain,
+            ..RunOptions::default()
+        };
+        let metadata = Metadata::load(
+            state,
+            &project_path,
+            self.cargo_env.as_ref(),
+            true,
+            &options,
+        )?;
+        let root_path = metadata.workspace_root.join("Cargo.toml");
+        let member_name = |manifest_path: &std::path::Path| {
+            metadata
+                .packages
+                .iter()
+                .find(|package| {
+                    package.manifest_path == manifest_path && metadata.is_member(package)
+                })
+                .map(|package| package.name.clone())
+        };
+        let mut manifests = vec![Manifest::load(root_path.clone(), member_name(&root_path))?];
+        for package in &metadata.packages {
+            if metadata.is_member(package) && package.manifest_path != root_path {
+                manifests.push(Manifest::load(
+                    package.manifest_path.clone(),
+                    Some(package.name.clone()),
+                )?);
+            }
+        }
+
+        let mut result = format!(
+            "=== cargo workspace deps ===\n📁 Working directory: {}\n🏢 Workspace manifest: {}\n",
+            project_path.display(),
+            root_path.display()
+        );
+
+        if action == "report" {
+            let declared = declarations(&manifests);
+            let workspace = workspace_dependencies(&manifests[0]);
+            let report = report(&declared, &workspace, min_members);
+            render_report(&mut result, &report, workspace.len(), min_members);
+            return Ok(fit_output(&result, limits, "a larger max_output_bytes"));
+        }
+
+        let mut promotions = Vec::new();
+        let mut skipped = Vec::new();
+        if action == "promote" {
+            for name in &names {
+                match promote(&mut manifests, 0, name, self.version.as_deref()) {
+                    Ok(promotion) => promotions.push(promotion),
+                    Err(e) => skipped.push(Skipped {
+                        name: name.clone(),
+                        reason: e.to_string(),
+                    }),
+                }
+            }
+        } else {
+            let declared = declarations(&manifests);
+            let workspace = workspace_dependencies(&manifests[0]);
+            let report = report(&declared, &workspace, min_members);
+            let candidates = report
+                .shared
+                .keys()
+                .copied()
+                .chain(
+                    report
+                        .inheritable
+                        .iter()
+                        .map(|declaration| declaration.key.as_str()),
+                )
+                .filter(|name| names.is_empty() || names.iter().any(|wanted| wanted == name))
+                .map(str::to_string)
+                .collect::<BTreeSet<_>>();
+            for name in candidates {
+                match promote(&mut manifests, 0, &name, None) {
+                    Ok(promotion) => promotions.push(promotion),
+                    Err(e) => skipped.push(Skipped {
+                        name,
+                        reason: e.to_string(),
+                    }),
+                }
+            }
+        }
+
+        let changes = manifests
+            .iter()
+            .filter(|manifest| manifest.changed())
+            .map(|manifest| FileChange {
+                path: manifest.path.clone(),
+                before: manifest.original.clone(),
+                after: manifest.document.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let dry_run = self.dry_run.unwrap_or(false);
+        if !dry_run {
+            for change in &changes {
+                fs::write(&change.path, &change.after)?;
+            }
+        }
+
+        render_promotions(&mut result, &promotions, &skipped);
+        result.push('\n');
+        if changes.is_empty() {
+            result.push_str("ℹ️  No manifests were changed\n");
+        } else {
+            if dry_run {
+                result.push_str("🔍 Dry run: nothing was written\n");
+            }
+            push_changes(
+                &mut result,
+                if dry_run {
+                    "📝 Would change"
+                } else {
+                    "📝 Changed"
+                },
+                &changes,
+                &metadata.workspace_root,
+                self.diff.unwrap_or(true),
+            );
+        }
+
+        #[derive(Serialize)]
+        struct Structured<'a> {
+            promoted: &'a [Promotion],
+            #[serde(skip_serializing_if = "<[Skipped]>::is_empty")]
+            skipped: &'a [Skipped],
+        }
+        result.push_str("\n🧾 Structured changes (JSON):\n");
+        result.push_str(
+            &serde_json::to_string(&Structured {
+                promoted: &promotions,
+                skipped: &skipped,
+            })
+            .unwrap_or_default(),
+        );
+        result.push('\n');
+
+        Ok(fit_output(&result, limits, "a larger max_output_bytes"))
+    }
+}
+
+fn report<'a>(
+    declared: &'a [Declaration],
+    workspace: &BTreeMap<String, crate::tools::workspace_deps::DependencySpec>,
+    min_members: usize,
+) -> Report<'a> {
+    let mut by_name: BTreeMap<&str, Vec<&Declaration>> = BTreeMap::new();
+    for declaration in declared {
+        if !declaration.spec.workspace {
+            by_name
+                .entry(&declaration.key)
+                .or_default()
+                .push(declaration);
+        }
+    }
+
+    let mut report = Report::default();
+    for (name, declarations) in by_name {
+        let versions = declarations
+            .iter()
+            .filter_map(|declaration| declaration.spec.version.as_deref())
+            .collect::<BTreeSet<_>>();
+        if versions.len() > 1 {
+            report.drift.insert(name, declarations.clone());
+        }
+        if workspace.contains_key(name) {
+            report.inheritable.extend(declarations);
+        } else {
+            let members = declarations
+                .iter()
+                .map(|declaration| &declaration.member)
+                .collect::<BTreeSet<_>>();
+            if members.len() >= min_members.max(1) {
+                report.shared.insert(name, declarations);
+            }
+        }
+    }
+    report
+}
+
+fn describe(declaration: &Declaration) -> String {
+    format!(
+        "{} [{}] {}",
+        declaration.member,
+        declaration.table,
+        declaration.spec.describe()
+    )
+}
+
+fn render_report(result: &mut String, report: &Report, entries: usize, min_members: usize) {
+    let _ = writeln!(
+        result,
+        "📚 [workspace.dependencies]: {entries} entr{}",
+        if entries == 1 { "y" } else { "ies" }
+    );
+
+    if report.drift.is_empty() {
+        result.push_str("\n✅ No version drift between members\n");
+    } else {
+        let _ = writeln!(result, "\n⚠️  Version drift ({}):", report.drift.len());
+        for (name, declarations) in &report.drift {
+            let _ = writeln!(result, "   {name}:");
+            for declaration in declarations {
+                let _ = writeln!(result, "      {}", describe(declaration));
+            }
+        }
+    }
+
+    if !report.shared.is_empty() {
+        let _ = writeln!(
+            result,
+            "\n👥 Declared by {min_members} or more members, not in the workspace table ({}):",
+            report.shared.len()
+        );
+        for (name, declarations) in &report.shared {
+            let members = declarations
+                .iter()
+                .map(|declaration| declaration.member.as_str())
+                .collect::<BTreeSet<_>>();
+            let _ = writeln!(
+                result,
+                "   {name}: {}",
+                members.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    if !report.inheritable.is_empty() {
+        let _ = writeln!(
+            result,
+            "\n🔗 Could inherit from the workspace table ({}):",
+            report.inheritable.len()
+        );
+        for declaration in &report.inheritable {
+            let _ = writeln!(
+                result,
+                "   {} in {}",
+                declaration.key,
+                describe(declaration)
+            );
+        }
+    }
+
+    if !report.shared.is_empty() || !report.inheritable.is_empty() {
+        result.push_str("\n💡 Use action \"consolidate\" to move these to the workspace table\n");
+    }
+
+    result.push_str("\n🧾 Structured report (JSON):\n");
+    result.push_str(&serde_json::to_string(report).unwrap_or_default());
+    result.push('\n');
+}
+
+fn render_promotions(result: &mut String, promotions: &[Promotion], skipped: &[Skipped]) {
+    if !promotions.is_empty() {
+        result.push_str("\n📦 Workspace dependencies:\n");
+    }
+    for promotion in promotions {
+        let _ = writeln!(
+            result,
+            "   {} {} = {}",
+            if promotion.existing { "🔗" } else { "➕" },
+            promotion.name,
+            promotion.entry.describe()
+        );
+        for declaration in &promotion.declarations {
+            let drifted = declaration.spec.version.is_some()
+                && declaration.spec.version != promotion.entry.version;
+            let _ = writeln!(
+                result,
+                "      {} {}",
+                if drifted { "⚠️ " } else { "✏️ " },
+                describe(declaration)
+            );
+        }
+        for note in &promotion.notes {
+            let _ = writeln!(result, "      ℹ️  {note}");
+        }
+    }
+
+    if !skipped.is_empty() {
+        let _ = writeln!(result, "\n⏭️  Skipped ({}):", skipped.len());
+        for skipped in skipped {
+            let _ = writeln!(result, "   {}: {}", skipped.name, skipped.reason);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, str::FromStr};
+
+/// Features to activate, as a list or as one string of names separated by spaces or commas
+/// (the way cargo's `--features` takes them)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Features {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl Features {
+    /// The individual feature names
+    pub fn names(&self) -> Vec<String> {
+        let items = match self {
+            Self::List(items) => items.as_slice(),
+            Self::Joined(joined) => std::slice::from_ref(joined),
+        };
+        items
+            .iter()
+            .flat_map(|item| item.split([' ', ',']))
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl FromStr for Features {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::Joined(s.to_string()))
+    }
+}
+
+/// Package, feature, profile and platform selection shared by every tool that compiles the
+/// project. Flattened into each tool's parameters.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+pub struct CompileOptions {
+    /// Optional package to compile (for workspaces)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// Features to activate, as a list or a space- or comma-separated string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub features: Option<Features>,
+
+    /// Activate all features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_features: Option<bool>,
+
+    /// Do not activate the default feature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub no_default_features: Option<bool>,
+
+    /// Build with the release profile (optimized)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub release: Option<bool>,
+
+    /// Build with a named profile from Cargo.toml instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Target triple to compile for (e.g., 'wasm32-unknown-unknown')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub target: Option<String>,
+}
+
+impl CompileOptions {
+    /// The cargo arguments for these options
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if let Some(ref package) = self.package {
+            args.extend(["--package".into(), package.clone()]);
+        }
+
+        let features = self.features.as_ref().map(Features::names);
+        if let Some(features) = features
+            && !features.is_empty()
+        {
+            args.extend(["--features".into(), features.join(",")]);
+        }
+
+        if self.all_features.unwrap_or(false) {
+            args.push("--all-features".into());
+        }
+
+        if self.no_default_features.unwrap_or(false) {
+            args.push("--no-default-features".into());
+        }
+
+        if self.release.unwrap_or(false) {
+            args.push("--release".into());
+        }
+
+        if let Some(ref profile) = self.profile {
+            args.extend(["--profile".into(), profile.clone()]);
+        }
+
+        if let Some(ref target) = self.target {
+            args.extend(["--target".into(), target.clone()]);
+        }
+
+        args
+    }
+}
+
+/// Which workspace packages, and which of their targets, to compile. Flattened into the
+/// parameters of tools that can compile more than one target.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+pub struct TargetSelection {
+    /// Compile every package in the workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub workspace: Option<bool>,
+
+    /// Packages to leave out when compiling the whole workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub exclude: Option<Vec<String>>,
+
+    /// Only the library target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub lib: Option<bool>,
+
+    /// All binary targets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub bins: Option<bool>,
+
+    /// All test targets, including unit tests in the library and binaries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub tests: Option<bool>,
+
+    /// All targets: library, binaries, tests, examples and benches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub all_targets: Option<bool>,
+}
+
+impl TargetSelection {
+    /// The cargo arguments for this selection
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.workspace.unwrap_or(false) {
+            args.push("--workspace".into());
+        }
+
+        for package in self.exclude.iter().flatten() {
+            args.extend(["--exclude".into(), package.clone()]);
+        }
+
+        if self.lib.unwrap_or(false) {
+            args.push("--lib".into());
+        }
+
+        if self.bins.unwrap_or(false) {
+            args.push("--bins".into());
+        }
+
+        if self.tests.unwrap_or(false) {
+            args.push("--tests".into());
+        }
+
+        if self.all_targets.unwrap_or(false) {
+            args.push("--all-targets".into());
+        }
+
+        args
+    }
+}
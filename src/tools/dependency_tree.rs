@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+/// One entry in the dependency tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreeNode {
+    pub name: String,
+    /// Absent for feature nodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The feature this node stands for, when showing feature edges
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    /// Anything cargo printed in parentheses, e.g. a local path or "proc-macro"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Whether this node was already shown elsewhere, so its dependencies are left out here
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    /// Parse one entry, without its depth prefix: `name v1.2.3 (notes) (*)` or
+    /// `name feature "default"`
+    fn parse(entry: &str) -> Self {
+        let mut rest = entry.trim();
+        let repeated = match rest.strip_suffix(" (*)") {
+            Some(stripped) => {
+                rest = stripped;
+                true
+            }
+            None => false,
+        };
+
+        let (name, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        let mut node = Self {
+            name: name.to_string(),
+            version: None,
+            feature: None,
+            notes: Vec::new(),
+            repeated,
+            children: Vec::new(),
+        };
+
+        if let Some(feature) = rest.strip_prefix("feature ") {
+            node.feature = Some(feature.trim_matches('"').to_string());
+            return node;
+        }
+
+        let (version, mut notes) = rest.split_once(' ').unwrap_or((rest, ""));
+        node.version = Some(version.strip_prefix('v').unwrap_or(version).to_string());
+        while let Some(note) = notes.trim_start().strip_prefix('(')
+            && let Some((note, remaining)) = note.split_once(')')
+        {
+            node.notes.push(note.to_string());
+            notes = remaining;
+        }
+        node
+    }
+}
+
+/// Parse `cargo tree --prefix depth` output, in which each line starts with its depth, into
+/// one tree per root. Several roots are printed for `--workspace`, `--invert` and
+/// `--duplicates`.
+pub fn parse_tree(stdout: &str) -> Vec<TreeNode> {
+    let mut roots = Vec::new();
+    // The path from the current root down to the last node read
+    let mut stack: Vec<TreeNode> = Vec::new();
+
+    fn attach(stack: &mut Vec<TreeNode>, roots: &mut Vec<TreeNode>, depth: usize) {
+        while stack.len() > depth {
+            let node = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => roots.push(node),
+            }
+        }
+    }
+
+    for line in stdout.lines() {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        let Ok(depth) = line[..digits].parse::<usize>() else {
+            continue;
+        };
+        // Never attach deeper than one level below the current node, even if cargo skips one
+        let depth = depth.min(stack.len());
+        attach(&mut stack, &mut roots, depth);
+        stack.push(TreeNode::parse(&line[digits..]));
+    }
+    attach(&mut stack, &mut roots, 0);
+
+    roots
+}
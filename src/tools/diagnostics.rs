@@ -0,0 +1,385 @@
+use crate::tools::cargo_utils::{CargoOutput, push_section};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Write, path::Path};
+
+/// Argument that switches cargo's compiler output to line-delimited JSON
+pub const MESSAGE_FORMAT_JSON: &str = "--message-format=json";
+
+/// A compiler diagnostic, condensed from rustc's JSON output
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity, e.g. "error" or "warning"
+    pub level: String,
+    /// Error or lint code, e.g. "E0308" or "clippy::needless_borrow"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// The main diagnostic message
+    pub message: String,
+    /// Location of the primary span
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Labelled spans attached to the diagnostic
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    /// Notes, help messages and suggestions
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Child>,
+}
+
+/// A position in a source file
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A labelled source span
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Label {
+    #[serde(flatten)]
+    pub location: Location,
+    pub text: String,
+    pub primary: bool,
+}
+
+/// A note, help or suggestion attached to a diagnostic
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Child {
+    pub level: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Replacement text suggested by the compiler, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RawDiagnostic>,
+}
+
+#[derive(Deserialize)]
+struct RawDiagnostic {
+    rendered: Option<String>,
+    message: String,
+    level: String,
+    code: Option<RawCode>,
+    #[serde(default)]
+    spans: Vec<RawSpan>,
+    #[serde(default)]
+    children: Vec<RawDiagnostic>,
+}
+
+#[derive(Deserialize)]
+struct RawCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RawSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+}
+
+impl RawSpan {
+    fn location(&self) -> Location {
+        Location {
+            file: self.file_name.clone(),
+            line: self.line_start,
+            column: self.column_start,
+        }
+    }
+}
+
+impl From<RawDiagnostic> for Diagnostic {
+    fn from(raw: RawDiagnostic) -> Self {
+        let location = raw
+            .spans
+            .iter()
+            .find(|span| span.is_primary)
+            .map(RawSpan::location);
+
+        let labels = raw
+            .spans
+            .iter()
+            .filter_map(|span| {
+                span.label.as_ref().map(|text| Label {
+                    location: span.location(),
+                    text: text.clone(),
+                    primary: span.is_primary,
+                })
+            })
+            .collect();
+
+        let children = raw
+            .children
+            .into_iter()
+            .map(|child| {
+                let primary = child
+                    .spans
+                    .iter()
+                    .find(|span| span.is_primary)
+                    .or(child.spans.first());
+                Child {
+                    level: child.level,
+                    message: child.message,
+                    location: primary.map(RawSpan::location),
+                    suggestion: primary.and_then(|span| span.suggested_replacement.clone()),
+                }
+            })
+            .collect();
+
+        Self {
+            level: raw.level,
+            code: raw.code.map(|code| code.code),
+            message: raw.message,
+            location,
+            labels,
+            children,
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Compiler summaries like "aborting due to 2 previous errors" or "3 warnings emitted"
+    /// carry no information beyond the diagnostics themselves
+    fn is_summary(&self) -> bool {
+        self.level == "failure-note"
+            || self.location.is_none()
+                && (self.message.starts_with("aborting due to")
+                    || self.message.ends_with("warning emitted")
+                    || self.message.ends_with("warnings emitted")
+                    || self.message.starts_with("For more information about"))
+    }
+
+    /// Write the diagnostic as a few lines of text: message, location, labels and notes
+    pub fn render(&self, out: &mut String) {
+        let _ = match &self.code {
+            Some(code) => writeln!(out, "{}[{code}]: {}", self.level, self.message),
+            None => writeln!(out, "{}: {}", self.level, self.message),
+        };
+
+        if let Some(location) = &self.location {
+            let _ = writeln!(out, "  --> {location}");
+        }
+
+        for label in &self.labels {
+            if label.primary && Some(&label.location) == self.location.as_ref() {
+                let _ = writeln!(out, "  label: {}", label.text);
+            } else {
+                let _ = writeln!(out, "  label ({}): {}", label.location, label.text);
+            }
+        }
+
+        for child in &self.children {
+            let _ = write!(out, "  = {}: {}", child.level, child.message);
+            if let Some(suggestion) = &child.suggestion {
+                let _ = write!(out, " `{suggestion}`");
+            }
+            if let Some(location) = &child.location
+                && Some(location) != self.location.as_ref()
+            {
+                let _ = write!(out, " ({location})");
+            }
+            out.push('\n');
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Split cargo's JSON stdout into compiler diagnostics and any lines that were not cargo
+/// messages (e.g. output from a build script or the program under test)
+pub fn parse_messages(stdout: &str) -> (Vec<Diagnostic>, String) {
+    let mut diagnostics = Vec::new();
+    let mut seen = HashSet::new();
+    let mut other = String::new();
+
+    for line in stdout.lines() {
+        match serde_json::from_str::<CargoMessage>(line) {
+            Ok(CargoMessage {
+                reason,
+                message: Some(raw),
+            }) if reason == "compiler-message" => {
+                let diagnostic = Diagnostic::from(raw);
+                if diagnostic.is_summary() {
+                    continue;
+                }
+
+                // cargo reports the same diagnostic once per target that includes the file
+                let key = (
+                    diagnostic.level.clone(),
+                    diagnostic.code.clone(),
+                    diagnostic.message.clone(),
+                    diagnostic.location.clone(),
+                );
+                if seen.insert(key) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+            Ok(_) => {}
+            Err(_) => {
+                other.push_str(line);
+                other.push('\n');
+            }
+        }
+    }
+
+    (diagnostics, other)
+}
+
+/// Convert a line of cargo's JSON stdout into what cargo would have printed to a terminal:
+/// compiler messages become their rendered text, other cargo messages are dropped and
+/// non-JSON lines pass through unchanged
+pub fn human_readable_line(line: &str) -> Option<String> {
+    match serde_json::from_str::<CargoMessage>(line) {
+        Ok(CargoMessage {
+            reason,
+            message:
+                Some(RawDiagnostic {
+                    rendered: Some(rendered),
+                    ..
+                }),
+        }) if reason == "compiler-message" => Some(rendered.trim_end().to_string()),
+        Ok(_) => None,
+        Err(_) => Some(line.to_string()),
+    }
+}
+
+/// Render a one-line count of diagnostics by level, e.g. "2 errors, 1 warning"
+pub fn summarize(diagnostics: &[Diagnostic]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for diagnostic in diagnostics {
+        match counts
+            .iter_mut()
+            .find(|(level, _)| *level == diagnostic.level)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&diagnostic.level, 1)),
+        }
+    }
+
+    if counts.is_empty() {
+        return "no errors or warnings".into();
+    }
+
+    counts.sort_by_key(|(level, _)| match *level {
+        "error" | "error: internal compiler error" => 0,
+        "warning" => 1,
+        _ => 2,
+    });
+
+    counts
+        .iter()
+        .map(|(level, count)| {
+            let noun = match *level {
+                "error: internal compiler error" => "internal compiler error",
+                "failure-note" => "failure note",
+                "help" => "help message",
+                level => level,
+            };
+            format!("{count} {noun}{}", if *count == 1 { "" } else { "s" })
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How often one lint (or error code) fired at one level
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct LintCount {
+    pub lint: String,
+    pub level: String,
+    pub count: usize,
+}
+
+/// Group diagnostics that have a code by that code and their level, most frequent first
+pub fn count_by_lint(diagnostics: &[Diagnostic]) -> Vec<LintCount> {
+    let mut counts: Vec<LintCount> = Vec::new();
+    for diagnostic in diagnostics {
+        let Some(code) = &diagnostic.code else {
+            continue;
+        };
+        match counts
+            .iter_mut()
+            .find(|count| count.lint == *code && count.level == diagnostic.level)
+        {
+            Some(count) => count.count += 1,
+            None => counts.push(LintCount {
+                lint: code.clone(),
+                level: diagnostic.level.clone(),
+                count: 1,
+            }),
+        }
+    }
+
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.lint.cmp(&b.lint)));
+    counts
+}
+
+/// Format a finished JSON-mode cargo command as a compact rendered summary followed by the
+/// structured diagnostics
+pub fn format_diagnostics_output(
+    output: &CargoOutput,
+    command_name: &str,
+    project_path: &Path,
+) -> String {
+    render_diagnostics(output, command_name, project_path, false)
+}
+
+/// Like [`format_diagnostics_output`], with a count of how often each lint fired before the
+/// individual diagnostics
+pub fn format_lint_output(output: &CargoOutput, command_name: &str, project_path: &Path) -> String {
+    render_diagnostics(output, command_name, project_path, true)
+}
+
+fn render_diagnostics(
+    output: &CargoOutput,
+    command_name: &str,
+    project_path: &Path,
+    by_lint: bool,
+) -> String {
+    let (diagnostics, other_stdout) = parse_messages(&output.stdout);
+
+    let mut result = output.header(command_name, project_path);
+    result.push_str(&format!("📋 Diagnostics: {}\n\n", summarize(&diagnostics)));
+
+    let counts = if by_lint {
+        count_by_lint(&diagnostics)
+    } else {
+        Vec::new()
+    };
+    if !counts.is_empty() {
+        result.push_str("📊 By lint:\n");
+        for LintCount { lint, level, count } in &counts {
+            let _ = writeln!(result, "   {count:>4} × {lint} ({level})");
+        }
+        result.push('\n');
+    }
+
+    if !diagnostics.is_empty() {
+        for diagnostic in &diagnostics {
+            diagnostic.render(&mut result);
+            result.push('\n');
+        }
+
+        result.push_str("🧾 Structured diagnostics (JSON):\n");
+        result.push_str(&serde_json::to_string(&diagnostics).unwrap_or_default());
+        result.push_str("\n\n");
+    }
+
+    push_section(&mut result, "📤 STDOUT:", &other_stdout);
+    push_section(&mut result, "📤 STDERR:", &output.stderr);
+
+    result
+}
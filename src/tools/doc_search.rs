@@ -0,0 +1,198 @@
+use crate::runs::Invocation;
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::RunOptions;
+use crate::tools::metadata::Metadata;
+use crate::tools::rustdoc::{DocIndex, DocSource};
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Write, path::PathBuf};
+
+/// Look up items in documentation built with cargo_doc, returning their signatures and docs
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "doc_search")]
+pub struct DocSearch {
+    /// An item path such as `serde_json::Value` or `Vec::push`, or part of an item's name
+    /// (matched fuzzily, ignoring case)
+    pub query: String,
+
+    /// Path to the project directory or any directory inside it (supports `~`). Defaults to the
+    /// project selected with set_working_directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Only search this crate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub crate_name: Option<String>,
+
+    /// Only return items of this kind: "struct", "enum", "trait", "fn", "method", "mod",
+    /// "macro", "type", "constant", "static", "variant", ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Maximum number of items to return (default: 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Include complete doc comments rather than their first paragraph (default: only when
+    /// there is a single match)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub full_docs: Option<bool>,
+
+    /// Documentation directory to search (defaults to the doc directory in the workspace's
+    /// target directory, as reported by cargo metadata)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub doc_dir: Option<String>,
+
+    /// Optional timeout in seconds for finding the target directory, after which cargo and
+    /// everything it started is killed (defaults to CARGO_MCP_DEFAULT_TIMEOUT, if set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Optional Rust toolchain to use (e.g., 'stable', 'nightly', '1.70.0')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub toolchain: Option<String>,
+
+    /// Optional environment variables to set for the cargo command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(skip)]
+    pub cargo_env: Option<HashMap<String, String>>,
+}
+
+impl WithExamples for DocSearch {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Look up a type by its path",
+                item: Self {
+                    query: "serde_json::Value".into(),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Find a method on a type",
+                item: Self {
+                    query: "Command::current_dir".into(),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Find functions in one crate whose name resembles a word",
+                item: Self {
+                    query: "from_str".into(),
+                    crate_name: Some("toml".into()),
+                    kind: Some("fn".into()),
+                    limit: Some(10),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for DocSearch {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let invocation = Invocation::new("doc_search", &self);
+        let doc_dir = match &self.doc_dir {
+            Some(dir) => PathBuf::from(&*shellexpand::tilde(dir)),
+            None => {
+                let project_path = state.resolve_project(self.path.as_deref())?;
+
+                // Use toolchain from args, session default, or none
+                let toolchain = self
+                    .toolchain
+                    .clone()
+                    .or_else(|| state.get_default_toolchain().unwrap_or(None));
+
+                // Use timeout from args, session default, or none
+                let timeout_secs = self
+                    .timeout
+                    .or_else(|| state.get_default_timeout().unwrap_or(None));
+
+                // cargo knows where the target directory is, including one set in .cargo/config
+                let options = RunOptions {
+                    timeout_secs,
+                    limits: state.output_limits(None, None),
+                    invocation,
+                    toolchain,
+                    ..RunOptions::default()
+                };
+                let metadata = Metadata::load(
+                    state,
+                    &project_path,
+                    self.cargo_env.as_ref(),
+                    true,
+                    &options,
+                )?;
+                metadata.target_directory.join("doc")
+            }
+        };
+
+        let index = DocIndex::load(&doc_dir)?;
+        let items = index.search(
+            &self.query,
+            self.crate_name.as_deref(),
+            self.kind.as_deref(),
+            self.limit.unwrap_or(5),
+        )?;
+
+        let source = match index.source {
+            DocSource::SearchIndex | DocSource::Html => "HTML docs",
+            DocSource::Json => "rustdoc JSON",
+        };
+        if items.is_empty() {
+            return Ok(format!(
+                "🔎 Nothing matching \"{}\" in {} ({source})\n\nDocumented crates: {}\n",
+                self.query,
+                doc_dir.display(),
+                index.crates().join(", ")
+            ));
+        }
+
+        let full_docs = self.full_docs.unwrap_or(items.len() == 1);
+        let mut result = format!(
+            "🔎 {} item{} matching \"{}\" in {} ({source})\n",
+            items.len(),
+            if items.len() == 1 { "" } else { "s" },
+            self.query,
+            doc_dir.display()
+        );
+
+        for item in items {
+            let _ = writeln!(result, "\n📘 {} {}", item.kind, item.path);
+            if let Some(page) = &item.page {
+                let _ = writeln!(result, "📄 {}", page.display());
+            }
+            if let Some(signature) = &item.signature {
+                let _ = writeln!(result, "```rust\n{signature}\n```");
+            }
+            if let Some(docs) = &item.docs {
+                let docs = if full_docs {
+                    docs.as_str()
+                } else {
+                    docs.split("\n\n").next().unwrap_or_default()
+                };
+                let _ = writeln!(result, "{}", docs.trim());
+            }
+            if !item.members.is_empty() {
+                let _ = writeln!(result, "🧩 Members:");
+                for member in &item.members {
+                    let _ = writeln!(result, "   {member}");
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
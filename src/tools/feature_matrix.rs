@@ -0,0 +1,108 @@
+use serde::Serialize;
+use std::fmt;
+
+/// One set of features to compile a package with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combination {
+    /// The package's default features
+    Default,
+    /// No features at all
+    NoDefaultFeatures,
+    /// Exactly these features, without the defaults
+    Features(Vec<String>),
+    /// Every feature
+    AllFeatures,
+}
+
+impl Combination {
+    /// The cargo arguments that select this combination
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Self::Default => Vec::new(),
+            Self::NoDefaultFeatures => vec!["--no-default-features".into()],
+            Self::Features(features) => vec![
+                "--no-default-features".into(),
+                "--features".into(),
+                features.join(","),
+            ],
+            Self::AllFeatures => vec!["--all-features".into()],
+        }
+    }
+}
+
+impl fmt::Display for Combination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::NoDefaultFeatures => f.write_str("no-default-features"),
+            Self::Features(features) => f.write_str(&features.join(" + ")),
+            Self::AllFeatures => f.write_str("all-features"),
+        }
+    }
+}
+
+/// The combinations to check for a package with `features`: the defaults, no features, every
+/// subset of up to `depth` features and all features. Features in `skip` are left out of the
+/// subsets, and any combination that includes every feature of an `exclude` entry is dropped.
+/// Returns `None` if there are more than `max` combinations (0 for no limit), without listing
+/// them all first.
+pub fn combinations(
+    features: &[String],
+    skip: &[String],
+    exclude: &[Vec<String>],
+    depth: usize,
+    max: usize,
+) -> Option<Vec<Combination>> {
+    let excluded = |enabled: &[String]| {
+        exclude
+            .iter()
+            .any(|entry| !entry.is_empty() && entry.iter().all(|feature| enabled.contains(feature)))
+    };
+    let max = if max == 0 { usize::MAX } else { max };
+
+    let candidates = features
+        .iter()
+        .filter(|feature| *feature != "default" && !skip.contains(feature))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut result = vec![Combination::Default, Combination::NoDefaultFeatures];
+    for size in 1..=depth.min(candidates.len()) {
+        let complete = subsets(&candidates, size, &mut Vec::new(), &mut |subset| {
+            if !excluded(subset) {
+                result.push(Combination::Features(subset.to_vec()));
+            }
+            result.len() <= max
+        });
+        if !complete {
+            return None;
+        }
+    }
+    if !excluded(features) {
+        result.push(Combination::AllFeatures);
+    }
+    (result.len() <= max).then_some(result)
+}
+
+/// Visit every subset of `items` with `size` more elements than `chosen`, in order, until
+/// `visit` returns false. Returns whether every subset was visited.
+fn subsets(
+    items: &[String],
+    size: usize,
+    chosen: &mut Vec<String>,
+    visit: &mut impl FnMut(&[String]) -> bool,
+) -> bool {
+    if size == 0 {
+        return visit(chosen);
+    }
+    for (i, item) in items.iter().enumerate() {
+        chosen.push(item.clone());
+        let complete = subsets(&items[i + 1..], size - 1, chosen, visit);
+        chosen.pop();
+        if !complete {
+            return false;
+        }
+    }
+    true
+}
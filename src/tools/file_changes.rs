@@ -0,0 +1,127 @@
+use similar::TextDiff;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A file's contents before and after a tool rewrote it
+#[derive(Debug, Clone)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+}
+
+impl FileChange {
+    /// Number of lines added and removed
+    pub fn stats(&self) -> (usize, usize) {
+        let diff = TextDiff::from_lines(&self.before, &self.after);
+        diff.iter_all_changes()
+            .fold((0, 0), |(added, removed), change| match change.tag() {
+                similar::ChangeTag::Insert => (added + 1, removed),
+                similar::ChangeTag::Delete => (added, removed + 1),
+                similar::ChangeTag::Equal => (added, removed),
+            })
+    }
+
+    /// A unified diff with three lines of context, naming the file relative to `root`
+    pub fn unified_diff(&self, root: &Path) -> String {
+        let name = display_path(&self.path, root);
+        TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("a/{name}"), &format!("b/{name}"))
+            .to_string()
+    }
+}
+
+/// Read the current contents of files, leaving out any that can't be read
+pub fn snapshot(paths: impl IntoIterator<Item = PathBuf>) -> BTreeMap<PathBuf, String> {
+    paths
+        .into_iter()
+        .filter_map(|path| Some((path.clone(), fs::read_to_string(&path).ok()?)))
+        .collect()
+}
+
+/// Compare a snapshot with what the files contain now
+pub fn changes_since(snapshot: BTreeMap<PathBuf, String>) -> Vec<FileChange> {
+    snapshot
+        .into_iter()
+        .filter_map(|(path, before)| {
+            let after = fs::read_to_string(&path).unwrap_or_default();
+            (after != before).then_some(FileChange {
+                path,
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+fn display_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// List changed files with their line counts under `label`, followed by their unified diff
+/// if `diff` is set
+pub fn push_changes(
+    result: &mut String,
+    label: &str,
+    changes: &[FileChange],
+    root: &Path,
+    diff: bool,
+) {
+    let _ = writeln!(
+        result,
+        "{label} ({} file{}):",
+        changes.len(),
+        if changes.len() == 1 { "" } else { "s" }
+    );
+    for change in changes {
+        let (added, removed) = change.stats();
+        let _ = writeln!(
+            result,
+            "   {} (+{added} -{removed})",
+            display_path(&change.path, root)
+        );
+    }
+    result.push('\n');
+
+    if diff && !changes.is_empty() {
+        result.push_str("📋 Diff:\n```diff\n");
+        for change in changes {
+            result.push_str(&change.unified_diff(root));
+        }
+        result.push_str("```\n");
+    }
+}
+
+/// All Rust source files under `root`, leaving out build output and hidden directories
+pub fn rust_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() && !name.starts_with('.') && name != "target" => {
+                    dirs.push(path)
+                }
+                Ok(kind) if kind.is_file() && name.ends_with(".rs") => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
@@ -0,0 +1,155 @@
+use crate::state::CargoTools;
+use anyhow::{Result, anyhow};
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Read the full log of an earlier cargo command, by line range or matching a pattern
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "get_run_output")]
+pub struct GetRunOutput {
+    /// ID of the run, as given in a shortened response or by list_runs
+    pub run_id: u64,
+
+    /// Line to start reading from (use the `next offset` from a previous call to page through)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub offset: Option<usize>,
+
+    /// Only return the last N lines (ignores offset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub tail: Option<usize>,
+
+    /// Maximum number of lines to return (default: 200)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Only return lines matching this regular expression, with their line numbers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    /// Lines of context to show around each grep match (default: 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub context: Option<usize>,
+
+    /// Prefix each line with the time it arrived and whether it came from stdout or stderr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timestamps: Option<bool>,
+}
+
+impl WithExamples for GetRunOutput {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Read a run's log from the beginning",
+                item: Self {
+                    run_id: 1,
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Read the part of the log that was elided",
+                item: Self {
+                    run_id: 1,
+                    offset: Some(150),
+                    limit: Some(300),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Find every error in a run, with two lines of context",
+                item: Self {
+                    run_id: 1,
+                    grep: Some("^error".into()),
+                    context: Some(2),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for GetRunOutput {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let run = state.runs().get(self.run_id)?;
+        let log = run.read_log(self.timestamps.unwrap_or(false))?;
+        let limit = self.limit.unwrap_or(200);
+
+        let start = match self.tail {
+            Some(tail) => log.len().saturating_sub(tail),
+            None => self.offset.unwrap_or(0),
+        };
+
+        let mut result = format!("{run}\n\n");
+
+        let Some(pattern) = &self.grep else {
+            let end = start.saturating_add(limit).min(log.len()).max(start);
+            if start == end {
+                result.push_str("ℹ️  No output in the requested range\n");
+            } else {
+                result.push_str(&format!("📤 Lines {start}-{end} of {}:\n", log.len()));
+                for line in &log[start..end] {
+                    result.push_str(line);
+                    result.push('\n');
+                }
+            }
+            if end < log.len() {
+                result.push_str(&format!("\n➡️  Next offset: {end}\n"));
+            }
+            return Ok(result);
+        };
+
+        let regex = Regex::new(pattern).map_err(|e| anyhow!("Invalid grep pattern: {e}"))?;
+        let context = self.context.unwrap_or(0);
+        let matches = (start..log.len())
+            .filter(|&index| regex.is_match(&log[index]))
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            result.push_str(&format!(
+                "ℹ️  No lines matching {pattern:?} from line {start}\n"
+            ));
+            return Ok(result);
+        }
+
+        result.push_str(&format!(
+            "🔎 {} lines matching {pattern:?}{}:\n",
+            matches.len(),
+            if matches.len() > limit {
+                format!(", showing the first {limit}")
+            } else {
+                String::new()
+            }
+        ));
+
+        let mut last_shown = None;
+        for &index in matches.iter().take(limit) {
+            let first = index
+                .saturating_sub(context)
+                .max(last_shown.map_or(0, |last| last + 1));
+            let last = (index + context).min(log.len() - 1);
+            if last_shown.is_some_and(|shown| first > shown + 1) {
+                result.push_str("--\n");
+            }
+            for (number, line) in log.iter().enumerate().take(last + 1).skip(first) {
+                let separator = if number == index { ':' } else { '-' };
+                result.push_str(&format!("{number:>6}{separator} {line}\n"));
+            }
+            last_shown = Some(last);
+        }
+
+        if let Some(next) = matches.get(limit) {
+            result.push_str(&format!("\n➡️  Next offset: {next}\n"));
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::project::describe_project;
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+
+/// Show the Rust project that cargo commands currently operate on
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "get_working_directory")]
+pub struct GetWorkingDirectory {}
+
+impl WithExamples for GetWorkingDirectory {
+    fn examples() -> Vec<Example<Self>> {
+        vec![Example {
+            description: "Show the selected project",
+            item: Self {},
+        }]
+    }
+}
+
+impl Tool<CargoTools> for GetWorkingDirectory {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let selected = state.get_working_directory()?.is_some();
+        let package_root = state.ensure_rust_project()?;
+
+        let mut result = describe_project(&package_root);
+        if !selected {
+            result.push_str(
+                "ℹ️  No project selected with set_working_directory; using the server's current \
+                 directory\n",
+            );
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Cancel a running background job
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "job_cancel")]
+pub struct JobCancel {
+    /// ID of the job, as returned when it was started
+    pub job_id: u64,
+}
+
+impl WithExamples for JobCancel {
+    fn examples() -> Vec<Example<Self>> {
+        vec![Example {
+            description: "Cancel job 1",
+            item: Self { job_id: 1 },
+        }]
+    }
+}
+
+impl Tool<CargoTools> for JobCancel {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let job = state.jobs().get(self.job_id)?;
+
+        if !job.snapshot().state.is_running() {
+            return Ok(format!("ℹ️  Job is not running\n\n{}\n", job.snapshot()));
+        }
+
+        job.cancel()?;
+        // Give the waiter thread a moment to observe the exit
+        job.wait(Duration::from_secs(5));
+
+        Ok(format!("🛑 Cancelled\n\n{}\n", job.snapshot()))
+    }
+}
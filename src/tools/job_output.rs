@@ -0,0 +1,108 @@
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+
+/// Read the output of a background job so far
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "job_output")]
+pub struct JobOutput {
+    /// ID of the job, as returned when it was started
+    pub job_id: u64,
+
+    /// Line to start reading from (use the `next offset` from a previous call to follow output)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub offset: Option<usize>,
+
+    /// Only return the last N lines (ignores offset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub tail: Option<usize>,
+
+    /// Maximum number of lines to return (default: 200)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Prefix each line with the time it arrived and whether it came from stdout or stderr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timestamps: Option<bool>,
+}
+
+impl WithExamples for JobOutput {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Read output from the beginning",
+                item: Self {
+                    job_id: 1,
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Continue reading from where the last call left off",
+                item: Self {
+                    job_id: 1,
+                    offset: Some(200),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Show the last 20 lines",
+                item: Self {
+                    job_id: 1,
+                    tail: Some(20),
+                    ..Self::default()
+                },
+            },
+            Example {
+                description: "Show interleaved stdout and stderr with arrival times",
+                item: Self {
+                    job_id: 1,
+                    timestamps: Some(true),
+                    ..Self::default()
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for JobOutput {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let job = state.jobs().get(self.job_id)?;
+        let snapshot = job.snapshot();
+        let limit = self.limit.unwrap_or(200);
+
+        let start = match self.tail {
+            Some(tail) => snapshot.line_count.saturating_sub(tail),
+            None => self.offset.unwrap_or(0),
+        };
+        let end = start
+            .saturating_add(limit)
+            .min(snapshot.line_count)
+            .max(start);
+        let lines = job.lines(start, end, self.timestamps.unwrap_or(false));
+
+        let mut result = format!("{snapshot}\n\n");
+        if lines.is_empty() {
+            result.push_str("ℹ️  No output in the requested range\n");
+        } else {
+            result.push_str(&format!(
+                "📤 Lines {start}-{end} of {}:\n",
+                snapshot.line_count
+            ));
+            for line in lines {
+                result.push_str(&line);
+                result.push('\n');
+            }
+        }
+        result.push_str(&format!("\n➡️  Next offset: {end}\n"));
+
+        Ok(result)
+    }
+}
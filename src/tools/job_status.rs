@@ -0,0 +1,52 @@
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+
+/// Show the status of a background job, or list all background jobs
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "job_status")]
+pub struct JobStatus {
+    /// Optional job ID (omit to list all jobs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub job_id: Option<u64>,
+}
+
+impl WithExamples for JobStatus {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "List all background jobs",
+                item: Self::default(),
+            },
+            Example {
+                description: "Show the status of job 1",
+                item: Self { job_id: Some(1) },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for JobStatus {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let jobs = state.jobs();
+
+        if let Some(job_id) = self.job_id {
+            return Ok(format!("{}\n", jobs.get(job_id)?.snapshot()));
+        }
+
+        let snapshots = jobs.iter().map(|job| job.snapshot()).collect::<Vec<_>>();
+        if snapshots.is_empty() {
+            return Ok("ℹ️  No background jobs have been started\n".into());
+        }
+
+        Ok(snapshots
+            .iter()
+            .map(|snapshot| format!("{snapshot}\n"))
+            .collect())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Wait for a background job to finish and return its result
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "job_wait")]
+pub struct JobWait {
+    /// ID of the job, as returned when it was started
+    pub job_id: u64,
+
+    /// Maximum number of seconds to wait (default: 60)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+}
+
+impl WithExamples for JobWait {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Wait up to a minute for job 1",
+                item: Self {
+                    job_id: 1,
+                    timeout: None,
+                },
+            },
+            Example {
+                description: "Wait up to ten minutes for a long build",
+                item: Self {
+                    job_id: 2,
+                    timeout: Some(600),
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for JobWait {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let job = state.jobs().get(self.job_id)?;
+        let timeout = Duration::from_secs(self.timeout.unwrap_or(60));
+
+        match job.wait(timeout) {
+            Some(result) => Ok(result),
+            None => Ok(format!(
+                "⏳ Still running after waiting {}s\n\n{}\n",
+                timeout.as_secs(),
+                job.snapshot()
+            )),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+
+/// List earlier cargo commands, newest first, with their outcome and log location
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "list_runs")]
+pub struct ListRuns {
+    /// Only show runs of this tool (e.g. "cargo_test")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub tool: Option<String>,
+
+    /// Only show runs in this project directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Maximum number of runs to show (default: 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+impl WithExamples for ListRuns {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Show the most recent runs",
+                item: Self::default(),
+            },
+            Example {
+                description: "Show the last five test runs in one project",
+                item: Self {
+                    tool: Some("cargo_test".into()),
+                    path: Some("~/code/my-project".into()),
+                    limit: Some(5),
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for ListRuns {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let project_path = match &self.path {
+            Some(path) => Some(state.resolve_project(Some(path))?),
+            None => None,
+        };
+
+        let runs = state.runs().list()?;
+        let runs = runs
+            .iter()
+            .rev()
+            .filter(|run| self.tool.as_ref().is_none_or(|tool| &run.tool == tool))
+            .filter(|run| project_path.as_ref().is_none_or(|path| &run.cwd == path))
+            .take(self.limit.unwrap_or(20))
+            .collect::<Vec<_>>();
+
+        if runs.is_empty() {
+            return Ok("ℹ️  No matching runs recorded\n".to_string());
+        }
+
+        let mut result = String::new();
+        for run in runs {
+            result.push_str(&format!("{run}\n\n"));
+        }
+        result.push_str("Use get_run_output with a run_id to read a run's log.\n");
+        Ok(result)
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Result, anyhow};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+/// The packages recorded in a Cargo.lock
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// e.g. `registry+https://github.com/rust-lang/crates.io-index`. `None` for path packages,
+    /// including workspace members.
+    pub source: Option<String>,
+    /// Dependencies as "name", or "name version" when several versions are locked
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from crates.io, over git or sparse HTTP
+    pub fn is_from_crates_io(&self) -> bool {
+        self.source.as_deref().is_some_and(|source| {
+            source == "registry+https://github.com/rust-lang/crates.io-index"
+                || source == "sparse+https://index.crates.io/"
+        })
+    }
+}
+
+impl std::fmt::Display for LockedPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+impl Lockfile {
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).map_err(|e| anyhow!("Cannot parse Cargo.lock: {e}"))
+    }
+
+    /// Read the Cargo.lock in `workspace_root`
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        let path = workspace_root.join("Cargo.lock");
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            anyhow!(
+                "Cannot read {}: {e}. Run cargo_check or cargo_update to create it.",
+                path.display()
+            )
+        })?;
+        Self::parse(&contents)
+    }
+
+    /// Find the package a dependency entry refers to
+    fn resolve(&self, dependency: &str) -> Option<usize> {
+        let mut parts = dependency.split(' ');
+        let name = parts.next()?;
+        let version = parts.next();
+        self.packages.iter().position(|package| {
+            package.name == name && version.is_none_or(|version| package.version == version)
+        })
+    }
+
+    /// The shortest chain of dependencies from a path package (such as a workspace member) to
+    /// the package at `target`, both ends included
+    pub fn dependency_path(&self, target: usize) -> Vec<&LockedPackage> {
+        let mut previous: HashMap<usize, usize> = HashMap::new();
+        let mut queue = self
+            .packages
+            .iter()
+            .enumerate()
+            .filter(|(_, package)| package.source.is_none())
+            .map(|(index, _)| index)
+            .collect::<VecDeque<_>>();
+        let mut seen = queue.iter().copied().collect::<HashSet<_>>();
+
+        while let Some(index) = queue.pop_front() {
+            if index == target {
+                let mut path = vec![&self.packages[index]];
+                let mut current = index;
+                while let Some(&parent) = previous.get(&current) {
+                    path.push(&self.packages[parent]);
+                    current = parent;
+                }
+                path.reverse();
+                return path;
+            }
+            for dependency in &self.packages[index].dependencies {
+                if let Some(next) = self.resolve(dependency)
+                    && seen.insert(next)
+                {
+                    previous.insert(next, index);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        vec![&self.packages[target]]
+    }
+}
+
+/// What changed between two versions of a Cargo.lock. Versions of a crate that disappear are
+/// paired with versions that appear, highest with highest, as upgrades or downgrades.
+#[derive(Debug, Default, Serialize)]
+pub struct LockfileDiff {
+    pub added: Vec<VersionChange>,
+    pub removed: Vec<VersionChange>,
+    pub upgraded: Vec<VersionChange>,
+    pub downgraded: Vec<VersionChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionChange {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+impl Lockfile {
+    /// The packages added, removed, upgraded and downgraded in `after`
+    pub fn diff(&self, after: &Lockfile) -> LockfileDiff {
+        let versions = |lockfile: &Lockfile| {
+            let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for package in &lockfile.packages {
+                versions
+                    .entry(package.name.clone())
+                    .or_default()
+                    .push(package.version.clone());
+            }
+            versions
+        };
+        let (before, after) = (versions(self), versions(after));
+        let names = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+
+        let mut diff = LockfileDiff::default();
+        for name in names {
+            let old = before.get(name).map(Vec::as_slice).unwrap_or_default();
+            let new = after.get(name).map(Vec::as_slice).unwrap_or_default();
+            let mut gone = old
+                .iter()
+                .filter(|version| !new.contains(version))
+                .collect::<Vec<_>>();
+            let mut appeared = new
+                .iter()
+                .filter(|version| !old.contains(version))
+                .collect::<Vec<_>>();
+            gone.sort_by(|a, b| compare_versions(b, a));
+            appeared.sort_by(|a, b| compare_versions(b, a));
+
+            let paired = gone.len().min(appeared.len());
+            for (from, to) in gone.iter().zip(&appeared) {
+                let change = VersionChange {
+                    name: name.clone(),
+                    from: Some(from.to_string()),
+                    to: Some(to.to_string()),
+                };
+                if compare_versions(to, from) == Ordering::Less {
+                    diff.downgraded.push(change);
+                } else {
+                    diff.upgraded.push(change);
+                }
+            }
+            for version in &gone[paired..] {
+                diff.removed.push(VersionChange {
+                    name: name.clone(),
+                    from: Some(version.to_string()),
+                    to: None,
+                });
+            }
+            for version in &appeared[paired..] {
+                diff.added.push(VersionChange {
+                    name: name.clone(),
+                    from: None,
+                    to: Some(version.to_string()),
+                });
+            }
+        }
+        diff
+    }
+}
+
+impl LockfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.upgraded.is_empty()
+            && self.downgraded.is_empty()
+    }
+
+    /// Append a summary of the changes, one package per line, followed by the same changes as
+    /// JSON
+    pub fn render(&self, result: &mut String) {
+        if self.is_empty() {
+            result.push_str("🔒 Cargo.lock unchanged\n");
+            return;
+        }
+
+        let _ = writeln!(
+            result,
+            "🔒 Cargo.lock changes: {} added, {} removed, {} upgraded, {} downgraded",
+            self.added.len(),
+            self.removed.len(),
+            self.upgraded.len(),
+            self.downgraded.len()
+        );
+        for change in &self.added {
+            let _ = writeln!(
+                result,
+                "   ➕ {} {}",
+                change.name,
+                change.to.as_deref().unwrap_or_default()
+            );
+        }
+        for change in &self.removed {
+            let _ = writeln!(
+                result,
+                "   ➖ {} {}",
+                change.name,
+                change.from.as_deref().unwrap_or_default()
+            );
+        }
+        for (icon, changes) in [("⬆️ ", &self.upgraded), ("⬇️ ", &self.downgraded)] {
+            for change in changes {
+                let _ = writeln!(
+                    result,
+                    "   {icon} {} {} → {}",
+                    change.name,
+                    change.from.as_deref().unwrap_or_default(),
+                    change.to.as_deref().unwrap_or_default()
+                );
+            }
+        }
+
+        result.push_str("\n🧾 Structured lockfile changes (JSON):\n");
+        result.push_str(&serde_json::to_string(self).unwrap_or_default());
+        result.push('\n');
+    }
+}
+
+/// Compare versions by semver precedence, falling back to comparing the text for versions
+/// that don't parse
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
@@ -0,0 +1,206 @@
+use crate::state::CargoTools;
+use crate::tools::cargo_utils::{
+    OutputFormat, RunOptions, create_cargo_command, fit_run_output, run_in_foreground,
+};
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
+
+/// Output of `cargo metadata --format-version 1`, limited to the fields the tools use
+#[derive(Debug, Clone, Deserialize)]
+pub struct Metadata {
+    pub packages: Vec<Package>,
+    pub workspace_members: Vec<String>,
+    /// Absent when run with `--no-deps`
+    pub resolve: Option<Resolve>,
+    pub workspace_root: PathBuf,
+    pub target_directory: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub id: String,
+    pub edition: String,
+    /// SPDX license expression, e.g. `MIT OR Apache-2.0`
+    pub license: Option<String>,
+    /// Path of a license file, for packages without a license expression
+    pub license_file: Option<String>,
+    /// Where the package comes from, e.g. `registry+https://github.com/rust-lang/crates.io-index`.
+    /// `None` for path dependencies and workspace members.
+    pub source: Option<String>,
+    pub dependencies: Vec<Dependency>,
+    pub targets: Vec<Target>,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub manifest_path: PathBuf,
+}
+
+/// A dependency as declared in a manifest
+#[derive(Debug, Clone, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    pub req: String,
+    /// `None` for normal dependencies, otherwise "dev" or "build"
+    pub kind: Option<String>,
+    pub optional: bool,
+    pub uses_default_features: bool,
+    pub features: Vec<String>,
+    /// Platform the dependency is limited to, e.g. `cfg(windows)`
+    pub target: Option<String>,
+    /// Registry or git URL, e.g. `registry+https://github.com/rust-lang/crates.io-index`. `None`
+    /// for path dependencies.
+    pub source: Option<String>,
+}
+
+impl Dependency {
+    /// "normal", "dev" or "build"
+    pub fn kind_name(&self) -> &str {
+        self.kind.as_deref().unwrap_or("normal")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Target {
+    pub name: String,
+    /// e.g. `["lib"]`, `["bin"]`, `["test"]`, `["custom-build"]`
+    pub kind: Vec<String>,
+    pub src_path: PathBuf,
+    #[serde(default)]
+    pub required_features: Vec<String>,
+}
+
+/// The resolved dependency graph
+#[derive(Debug, Clone, Deserialize)]
+pub struct Resolve {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Node {
+    pub id: String,
+    pub deps: Vec<NodeDep>,
+    /// Features enabled for this package in the resolved build
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeDep {
+    pub pkg: String,
+}
+
+impl Metadata {
+    pub fn parse(stdout: &str) -> Result<Self> {
+        serde_json::from_str(stdout).map_err(|e| anyhow!("Cannot parse cargo metadata: {e}"))
+    }
+
+    /// Run `cargo metadata` for a tool that needs the workspace's layout but doesn't report on
+    /// the command itself. It runs with the tool's timeout and toolchain and is recorded in the
+    /// run history under the tool's name.
+    pub fn load(
+        state: &mut CargoTools,
+        project_path: &Path,
+        cargo_env: Option<&HashMap<String, String>>,
+        no_deps: bool,
+        options: &RunOptions,
+    ) -> Result<Self> {
+        let mut args = vec!["metadata", "--format-version", "1"];
+        if no_deps {
+            args.push("--no-deps");
+        }
+        let cmd = create_cargo_command(&args, options.toolchain.as_deref(), cargo_env);
+        let (output, full_output) = run_in_foreground(state, cmd, project_path, options)?;
+        if !output.succeeded() {
+            let result = OutputFormat::Plain.render(&output, "cargo metadata", project_path);
+            bail!(fit_run_output(
+                &result,
+                options.limits,
+                &full_output,
+                &output
+            ));
+        }
+        Self::parse(&output.stdout)
+    }
+
+    /// The package whose directory most closely contains `file`
+    pub fn package_for_file(&self, file: &Path) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|package| {
+                package
+                    .manifest_path
+                    .parent()
+                    .is_some_and(|dir| file.starts_with(dir))
+            })
+            .max_by_key(|package| package.manifest_path.components().count())
+    }
+
+    pub fn package(&self, id: &str) -> Option<&Package> {
+        self.packages.iter().find(|package| package.id == id)
+    }
+
+    pub fn is_member(&self, package: &Package) -> bool {
+        self.workspace_members.contains(&package.id)
+    }
+
+    /// The package's node in the resolved graph
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.resolve
+            .as_ref()?
+            .nodes
+            .iter()
+            .find(|node| node.id == id)
+    }
+
+    /// The package a declared dependency resolved to
+    pub fn resolved_dependency(
+        &self,
+        package: &Package,
+        dependency: &Dependency,
+    ) -> Option<&Package> {
+        self.node(&package.id)?
+            .deps
+            .iter()
+            .filter_map(|dep| self.package(&dep.pkg))
+            .find(|resolved| resolved.name == dependency.name)
+    }
+
+    /// The shortest chain of dependencies from a workspace member to the package with `id`,
+    /// both ends included
+    pub fn dependency_chain(&self, id: &str) -> Vec<&Package> {
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = self
+            .workspace_members
+            .iter()
+            .map(String::as_str)
+            .collect::<VecDeque<_>>();
+        let mut seen = queue.iter().copied().collect::<HashSet<_>>();
+
+        while let Some(current) = queue.pop_front() {
+            if current == id {
+                let mut chain = vec![current];
+                let mut current = current;
+                while let Some(&parent) = previous.get(current) {
+                    chain.push(parent);
+                    current = parent;
+                }
+                return chain
+                    .into_iter()
+                    .rev()
+                    .filter_map(|id| self.package(id))
+                    .collect();
+            }
+            for dep in self.node(current).into_iter().flat_map(|node| &node.deps) {
+                if seen.insert(&dep.pkg) {
+                    previous.insert(&dep.pkg, current);
+                    queue.push_back(&dep.pkg);
+                }
+            }
+        }
+
+        self.package(id).into_iter().collect()
+    }
+}
@@ -0,0 +1,461 @@
+use crate::tools::metadata::{Metadata, Package};
+use anyhow::{Result, anyhow, bail};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+const CRATES_IO_SPARSE_INDEX: &str = "https://index.crates.io/";
+
+/// A dependency policy, read from the subset of cargo-deny's `deny.toml` format that covers
+/// licenses, bans and sources
+#[derive(Debug, Default, Deserialize)]
+pub struct Policy {
+    /// Licenses are only checked when the policy has a `[licenses]` table
+    pub licenses: Option<LicensePolicy>,
+    #[serde(default)]
+    pub bans: BanPolicy,
+    #[serde(default)]
+    pub sources: SourcePolicy,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LicensePolicy {
+    /// SPDX identifiers of acceptable licenses
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Further licenses accepted for particular crates
+    #[serde(default)]
+    pub exceptions: Vec<LicenseException>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LicenseException {
+    #[serde(alias = "crate")]
+    pub name: String,
+    pub allow: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BanPolicy {
+    /// How to treat a crate that is locked at more than one version
+    #[serde(default = "Level::warn")]
+    pub multiple_versions: Level,
+    /// Crates that must not be used
+    #[serde(default)]
+    pub deny: Vec<CrateSpec>,
+    /// Crates whose duplicate versions are accepted
+    #[serde(default)]
+    pub skip: Vec<CrateSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourcePolicy {
+    /// How to treat crates from registries that are not in `allow_registry`
+    #[serde(default = "Level::warn")]
+    pub unknown_registry: Level,
+    /// How to treat crates from git repositories that are not in `allow_git`
+    #[serde(default = "Level::warn")]
+    pub unknown_git: Level,
+    #[serde(default = "default_registries")]
+    pub allow_registry: Vec<String>,
+    #[serde(default)]
+    pub allow_git: Vec<String>,
+}
+
+impl Default for SourcePolicy {
+    fn default() -> Self {
+        Self {
+            unknown_registry: Level::Warn,
+            unknown_git: Level::Warn,
+            allow_registry: default_registries(),
+            allow_git: Vec::new(),
+        }
+    }
+}
+
+fn default_registries() -> Vec<String> {
+    vec![CRATES_IO_INDEX.into()]
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+impl Level {
+    fn warn() -> Self {
+        Self::Warn
+    }
+}
+
+/// A crate named in a policy: `"name"`, `"name@version-req"` or
+/// `{ name = "name", version = "version-req" }`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CrateSpec {
+    Spec(String),
+    Table {
+        #[serde(alias = "crate")]
+        name: String,
+        version: Option<String>,
+    },
+}
+
+impl CrateSpec {
+    fn matches(&self, package: &Package) -> bool {
+        let (name, version) = match self {
+            Self::Spec(spec) => match spec.split_once('@') {
+                Some((name, version)) => (name, Some(version)),
+                None => (spec.as_str(), None),
+            },
+            Self::Table { name, version } => match name.split_once('@') {
+                Some((name, version)) => (name, Some(version)),
+                None => (name.as_str(), version.as_deref()),
+            },
+        };
+        name == package.name
+            && version.is_none_or(|version| {
+                let (Ok(requirement), Ok(version)) =
+                    (VersionReq::parse(version), Version::parse(&package.version))
+                else {
+                    return false;
+                };
+                requirement.matches(&version)
+            })
+    }
+}
+
+/// Something a dependency does that the policy doesn't allow
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    /// "licenses", "sources", "bans" or "duplicates"
+    pub check: &'static str,
+    /// "error" or "warning"
+    pub level: &'static str,
+    /// Name and version, e.g. "ring 0.17.8"
+    pub package: String,
+    pub message: String,
+    /// Dependency chains from a workspace member to the offending package, one per version
+    /// for duplicates
+    pub chains: Vec<Vec<String>>,
+}
+
+/// The checks a policy can run
+pub const CHECKS: [&str; 4] = ["licenses", "sources", "bans", "duplicates"];
+
+impl Policy {
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).map_err(|e| anyhow!("Cannot parse policy: {e}"))
+    }
+
+    /// Evaluate the policy against the dependencies in `metadata`, running only `checks`
+    pub fn check(&self, metadata: &Metadata, checks: &[&str]) -> Vec<Violation> {
+        let dependencies = metadata
+            .packages
+            .iter()
+            .filter(|package| !metadata.is_member(package))
+            .collect::<Vec<_>>();
+        let chain = |package: &Package| {
+            metadata
+                .dependency_chain(&package.id)
+                .iter()
+                .map(|package| format!("{} {}", package.name, package.version))
+                .collect::<Vec<_>>()
+        };
+        let violation =
+            |check: &'static str, level: &'static str, package: &Package, message: String| {
+                Violation {
+                    check,
+                    level,
+                    package: format!("{} {}", package.name, package.version),
+                    message,
+                    chains: vec![chain(package)],
+                }
+            };
+
+        let mut violations = Vec::new();
+
+        if checks.contains(&"licenses")
+            && let Some(licenses) = &self.licenses
+        {
+            for package in &dependencies {
+                let mut allow = licenses.allow.clone();
+                for exception in &licenses.exceptions {
+                    if exception.name == package.name {
+                        allow.extend(exception.allow.iter().cloned());
+                    }
+                }
+                let message = match &package.license {
+                    None => Some(match &package.license_file {
+                        Some(file) => format!(
+                            "No SPDX license expression, only a license file ({file}); add an \
+                             exception once it has been reviewed"
+                        ),
+                        None => "No license".into(),
+                    }),
+                    Some(license) => match LicenseExpr::parse(license) {
+                        Err(e) => Some(format!("Cannot parse license {license:?}: {e}")),
+                        Ok(expression) if !expression.allowed_by(&allow) => Some(format!(
+                            "{license}: {} not allowed",
+                            expression.disallowed(&allow).join(", ")
+                        )),
+                        Ok(_) => None,
+                    },
+                };
+                if let Some(message) = message {
+                    violations.push(violation("licenses", "error", package, message));
+                }
+            }
+        }
+
+        if checks.contains(&"sources") {
+            for package in &dependencies {
+                let Some(source) = &package.source else {
+                    continue;
+                };
+                let (level, message) = self.sources.check(source);
+                if let Some(level) = level.name() {
+                    violations.push(violation("sources", level, package, message));
+                }
+            }
+        }
+
+        if checks.contains(&"bans") {
+            for package in &dependencies {
+                if self.bans.deny.iter().any(|spec| spec.matches(package)) {
+                    violations.push(violation(
+                        "bans",
+                        "error",
+                        package,
+                        "Banned by the policy".into(),
+                    ));
+                }
+            }
+        }
+
+        if checks.contains(&"duplicates")
+            && let Some(level) = self.bans.multiple_versions.name()
+        {
+            let mut versions: BTreeMap<&str, Vec<&Package>> = BTreeMap::new();
+            for package in &metadata.packages {
+                if !self.bans.skip.iter().any(|spec| spec.matches(package)) {
+                    versions.entry(&package.name).or_default().push(package);
+                }
+            }
+            for (name, packages) in versions {
+                if packages.len() < 2 {
+                    continue;
+                }
+                violations.push(Violation {
+                    check: "duplicates",
+                    level,
+                    package: name.to_string(),
+                    message: format!(
+                        "{} versions: {}",
+                        packages.len(),
+                        packages
+                            .iter()
+                            .map(|package| package.version.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    chains: packages.iter().map(|package| chain(package)).collect(),
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+impl Level {
+    /// The violation level this policy level produces, if any
+    fn name(self) -> Option<&'static str> {
+        match self {
+            Self::Allow => None,
+            Self::Warn => Some("warning"),
+            Self::Deny => Some("error"),
+        }
+    }
+}
+
+impl SourcePolicy {
+    /// How a package source is treated, and why
+    fn check(&self, source: &str) -> (Level, String) {
+        if let Some(url) = source.strip_prefix("git+") {
+            let url = url.split(['?', '#']).next().unwrap_or(url);
+            let allowed = self
+                .allow_git
+                .iter()
+                .any(|allowed| normalize_url(allowed) == normalize_url(url));
+            return if allowed {
+                (Level::Allow, String::new())
+            } else {
+                (
+                    self.unknown_git,
+                    format!("Git source {url} is not in allow-git"),
+                )
+            };
+        }
+
+        let registry = source
+            .strip_prefix("registry+")
+            .or_else(|| source.strip_prefix("sparse+"))
+            .map(|url| {
+                if url == CRATES_IO_SPARSE_INDEX {
+                    CRATES_IO_INDEX
+                } else {
+                    url
+                }
+            });
+        match registry {
+            Some(url)
+                if self.allow_registry.iter().any(|allowed| {
+                    let allowed = allowed.strip_prefix("sparse+").unwrap_or(allowed);
+                    normalize_url(allowed) == normalize_url(url)
+                        || allowed == CRATES_IO_SPARSE_INDEX && url == CRATES_IO_INDEX
+                }) =>
+            {
+                (Level::Allow, String::new())
+            }
+            Some(url) => (
+                self.unknown_registry,
+                format!("Registry {url} is not in allow-registry"),
+            ),
+            None => (self.unknown_registry, format!("Unknown source {source}")),
+        }
+    }
+}
+
+fn normalize_url(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url)
+}
+
+/// A parsed SPDX license expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseExpr {
+    /// A license identifier, possibly with an exception, e.g. `Apache-2.0 WITH LLVM-exception`
+    License(String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    /// Parse an expression such as `(MIT OR Apache-2.0) AND Unicode-3.0`. The deprecated `/`
+    /// separator is read as `OR`.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let spaced = expression
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .replace('/', " OR ");
+        let tokens = spaced.split_whitespace().collect::<Vec<_>>();
+        let mut position = 0;
+        let parsed = parse_or(&tokens, &mut position)?;
+        if position != tokens.len() {
+            bail!("unexpected {:?}", tokens[position]);
+        }
+        Ok(parsed)
+    }
+
+    /// Whether the licenses in `allow` satisfy the expression
+    pub fn allowed_by(&self, allow: &[String]) -> bool {
+        match self {
+            Self::License(license) => license_allowed(license, allow),
+            Self::And(terms) => terms.iter().all(|term| term.allowed_by(allow)),
+            Self::Or(terms) => terms.iter().any(|term| term.allowed_by(allow)),
+        }
+    }
+
+    /// The licenses that keep the expression from being satisfied, leaving out alternatives
+    /// that don't matter because another one is allowed
+    pub fn disallowed(&self, allow: &[String]) -> Vec<&str> {
+        if self.allowed_by(allow) {
+            return Vec::new();
+        }
+        match self {
+            Self::License(license) => vec![license],
+            Self::And(terms) | Self::Or(terms) => terms
+                .iter()
+                .flat_map(|term| term.disallowed(allow))
+                .collect(),
+        }
+    }
+}
+
+fn license_allowed(license: &str, allow: &[String]) -> bool {
+    let base = license.split(" WITH ").next().unwrap_or(license);
+    allow
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(license) || allowed.eq_ignore_ascii_case(base))
+}
+
+fn parse_or(tokens: &[&str], position: &mut usize) -> Result<LicenseExpr> {
+    let mut terms = vec![parse_and(tokens, position)?];
+    while tokens
+        .get(*position)
+        .is_some_and(|token| token.eq_ignore_ascii_case("OR"))
+    {
+        *position += 1;
+        terms.push(parse_and(tokens, position)?);
+    }
+    Ok(if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        LicenseExpr::Or(terms)
+    })
+}
+
+fn parse_and(tokens: &[&str], position: &mut usize) -> Result<LicenseExpr> {
+    let mut terms = vec![parse_license(tokens, position)?];
+    while tokens
+        .get(*position)
+        .is_some_and(|token| token.eq_ignore_ascii_case("AND"))
+    {
+        *position += 1;
+        terms.push(parse_license(tokens, position)?);
+    }
+    Ok(if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        LicenseExpr::And(terms)
+    })
+}
+
+fn parse_license(tokens: &[&str], position: &mut usize) -> Result<LicenseExpr> {
+    let token = *tokens
+        .get(*position)
+        .ok_or_else(|| anyhow!("expression ends early"))?;
+    *position += 1;
+
+    if token == "(" {
+        let inner = parse_or(tokens, position)?;
+        if tokens.get(*position) != Some(&")") {
+            bail!("missing closing parenthesis");
+        }
+        *position += 1;
+        return Ok(inner);
+    }
+    if token == ")" || ["AND", "OR", "WITH"].contains(&token.to_uppercase().as_str()) {
+        bail!("unexpected {token:?}");
+    }
+
+    if tokens
+        .get(*position)
+        .is_some_and(|token| token.eq_ignore_ascii_case("WITH"))
+    {
+        let exception = tokens
+            .get(*position + 1)
+            .ok_or_else(|| anyhow!("missing exception after WITH"))?;
+        *position += 2;
+        return Ok(LicenseExpr::License(format!("{token} WITH {exception}")));
+    }
+    Ok(LicenseExpr::License(token.to_string()))
+}
@@ -0,0 +1,137 @@
+use anyhow::{Result, bail};
+use semver::Version;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A release of a crate as listed in a registry index
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexEntry {
+    pub vers: String,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+/// The crates.io index as cargo last saw it, or a local mirror of it
+#[derive(Debug, Clone)]
+pub enum RegistryIndex {
+    /// Cargo's own caches in `$CARGO_HOME/registry/index/*/.cache`, one per index cargo has used
+    /// for crates.io (sparse and git)
+    Cache(Vec<PathBuf>),
+    /// A directory in the registry index layout, such as a checkout of the crates.io index
+    Mirror(PathBuf),
+}
+
+impl RegistryIndex {
+    /// Use the mirror at `path`, or else the caches cargo keeps for crates.io
+    pub fn locate(path: Option<&Path>) -> Result<Self> {
+        if let Some(path) = path {
+            if !path.is_dir() {
+                bail!("No registry index at {}", path.display());
+            }
+            return Ok(Self::Mirror(path.to_path_buf()));
+        }
+
+        let index_dir = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+            .unwrap_or_default()
+            .join("registry")
+            .join("index");
+        let mut caches = fs::read_dir(&index_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.starts_with("index.crates.io-") || name.starts_with("github.com-")
+            })
+            .map(|entry| entry.path().join(".cache"))
+            .filter(|cache| cache.is_dir())
+            .collect::<Vec<_>>();
+        caches.sort();
+        if caches.is_empty() {
+            bail!(
+                "No crates.io index cache in {}. Run cargo_update or cargo_check once with \
+                 network access to fill it, or pass the path of a local index mirror.",
+                index_dir.display()
+            );
+        }
+        Ok(Self::Cache(caches))
+    }
+
+    pub fn paths(&self) -> Vec<&Path> {
+        match self {
+            Self::Cache(caches) => caches.iter().map(PathBuf::as_path).collect(),
+            Self::Mirror(path) => vec![path],
+        }
+    }
+
+    /// The releases of `name` the index knows about, or `None` if it has no entry for the crate
+    pub fn entries(&self, name: &str) -> Option<Vec<IndexEntry>> {
+        let relative = index_path(name);
+        let mut found = None;
+        for dir in self.paths() {
+            let Ok(bytes) = fs::read(dir.join(&relative)) else {
+                continue;
+            };
+            let entries = match self {
+                Self::Cache(_) => parse_cache(&bytes),
+                Self::Mirror(_) => parse_index_file(&String::from_utf8_lossy(&bytes)),
+            };
+            found.get_or_insert_with(Vec::new).extend(entries);
+        }
+        found
+    }
+
+    /// The newest releases of `name` that are not yanked, newest first. Pre-releases are only
+    /// included when `prerelease` is set.
+    pub fn versions(&self, name: &str, prerelease: bool) -> Option<Vec<Version>> {
+        let mut versions = self
+            .entries(name)?
+            .into_iter()
+            .filter(|entry| !entry.yanked)
+            .filter_map(|entry| Version::parse(&entry.vers).ok())
+            .filter(|version| prerelease || version.pre.is_empty())
+            .collect::<Vec<_>>();
+        versions.sort_by(|a, b| b.cmp(a));
+        versions.dedup();
+        Some(versions)
+    }
+}
+
+/// Where the index keeps a crate's file, e.g. `re/ge/regex`, `3/s/syn` or `1/a`
+pub fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Parse an index file: one JSON object per line
+pub fn parse_index_file(contents: &str) -> Vec<IndexEntry> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Parse one of cargo's index cache files: a cache version byte, the index format version as a
+/// little-endian u32 and a NUL-terminated revision, then NUL-terminated pairs of version and
+/// JSON entry. Files in an unknown format yield no entries.
+pub fn parse_cache(bytes: &[u8]) -> Vec<IndexEntry> {
+    if bytes.len() < 5 || bytes[0] != 3 {
+        return Vec::new();
+    }
+    bytes[5..]
+        .split(|byte| *byte == 0)
+        .skip(2)
+        .step_by(2)
+        .filter_map(|json| serde_json::from_slice(json).ok())
+        .collect()
+}
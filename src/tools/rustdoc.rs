@@ -0,0 +1,1053 @@
+use crate::tools::search_index;
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Directories rustdoc writes next to the crate directories
+const SHARED_DIRS: [&str; 5] = [
+    "src",
+    "static.files",
+    "trait.impl",
+    "type.impl",
+    "search.index",
+];
+
+/// Sections of a type's page that list trait and auto trait implementations, whose methods are
+/// left out of the type's own members
+const FOREIGN_IMPL_SECTIONS: [&str; 5] = [
+    "id=\"trait-implementations\"",
+    "id=\"synthetic-implementations\"",
+    "id=\"blanket-implementations\"",
+    "id=\"implementors\"",
+    "id=\"deref-methods",
+];
+
+/// Kinds of items whose pages list associated items or variants
+const HAS_MEMBERS: [&str; 5] = ["struct", "enum", "union", "trait", "primitive"];
+
+/// How many type pages a search for a member reads when the documentation has no search index
+const MAX_MEMBER_PAGES: usize = 100;
+
+/// Where an index entry's details come from
+#[derive(Debug, Clone)]
+enum Details {
+    /// A rendered item page, and the anchor of an associated item on it
+    Html {
+        page: PathBuf,
+        anchor: Option<String>,
+    },
+    /// Already rendered from rustdoc JSON
+    Json(Box<DocItem>),
+}
+
+/// A documented item found by a search
+#[derive(Debug, Clone, Default)]
+pub struct DocItem {
+    /// Full path, e.g. `serde_json::Value::as_str`
+    pub path: String,
+    /// "struct", "fn", "method", "trait", "mod", ...
+    pub kind: String,
+    pub signature: Option<String>,
+    /// Markdown-ish text of the doc comment
+    pub docs: Option<String>,
+    /// Signatures of fields, variants and inherent or trait methods
+    pub members: Vec<String>,
+    /// The rendered page, for HTML docs
+    pub page: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    path: String,
+    name: String,
+    kind: String,
+    details: Details,
+}
+
+/// Which kind of generated documentation an index was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocSource {
+    /// The HTML pages of `cargo doc`, found through rustdoc's search index
+    SearchIndex,
+    /// The HTML pages of `cargo doc`, found by walking the crate directories
+    Html,
+    /// `--output-format json` output of a nightly rustdoc
+    Json,
+}
+
+/// Every item in a documentation directory, searchable by path or name
+#[derive(Debug)]
+pub struct DocIndex {
+    pub source: DocSource,
+    entries: Vec<Entry>,
+}
+
+impl DocIndex {
+    /// Index the documentation in `doc_dir`, preferring rustdoc JSON when there is any
+    pub fn load(doc_dir: &Path) -> Result<Self> {
+        if !doc_dir.is_dir() {
+            return Err(anyhow!(
+                "No documentation found in {}. Build it with cargo_doc first.",
+                doc_dir.display()
+            ));
+        }
+
+        let mut json_files = fs::read_dir(doc_dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        json_files.sort();
+
+        let mut entries = Vec::new();
+        for file in &json_files {
+            let json: Value = serde_json::from_str(&fs::read_to_string(file)?)
+                .map_err(|e| anyhow!("Cannot parse {}: {e}", file.display()))?;
+            if json.get("format_version").is_some() {
+                index_json(&json, &mut entries);
+            }
+        }
+        if !entries.is_empty() {
+            return Ok(Self {
+                source: DocSource::Json,
+                entries,
+            });
+        }
+
+        let mut crate_dirs = Vec::new();
+        for entry in fs::read_dir(doc_dir)? {
+            let dir = entry?.path();
+            let is_crate = dir.is_dir()
+                && dir.join("index.html").is_file()
+                && !dir
+                    .file_name()
+                    .is_some_and(|name| SHARED_DIRS.iter().any(|shared| name == *shared));
+            if is_crate {
+                crate_dirs.push(dir);
+            }
+        }
+
+        // The search index is only used when it decodes and lists every documented crate;
+        // otherwise the HTML pages are walked instead
+        match search_index::load(doc_dir) {
+            Ok(Some(items)) if covers_crates(&items, &crate_dirs) => {
+                let mut entries = items
+                    .into_iter()
+                    .map(|item| Entry {
+                        path: item.path,
+                        name: item.name,
+                        kind: item.kind.to_string(),
+                        details: Details::Html {
+                            page: doc_dir.join(item.page),
+                            anchor: item.anchor,
+                        },
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort_by(|a, b| a.path.cmp(&b.path));
+                return Ok(Self {
+                    source: DocSource::SearchIndex,
+                    entries,
+                });
+            }
+            Ok(Some(_)) => log::warn!(
+                "the search index in {} doesn't list every crate",
+                doc_dir.display()
+            ),
+            Ok(None) => {}
+            Err(e) => log::warn!("cannot read the search index in {}: {e}", doc_dir.display()),
+        }
+
+        for dir in &crate_dirs {
+            let crate_name = dir.file_name().unwrap().to_string_lossy().to_string();
+            index_html_module(dir, &crate_name, &mut entries)?;
+        }
+        if entries.is_empty() {
+            return Err(anyhow!(
+                "No documented crates found in {}. Build them with cargo_doc first.",
+                doc_dir.display()
+            ));
+        }
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Self {
+            source: DocSource::Html,
+            entries,
+        })
+    }
+
+    /// Names of the documented crates
+    pub fn crates(&self) -> Vec<&str> {
+        let mut crates = self
+            .entries
+            .iter()
+            .map(|entry| entry.path.split("::").next().unwrap_or_default())
+            .collect::<Vec<_>>();
+        crates.sort_unstable();
+        crates.dedup();
+        crates
+    }
+
+    /// Find items matching `query`, best matches first. A query containing `::` is matched
+    /// against the end of each item's path, anything else against item names: exactly, then by
+    /// prefix, then as a substring, then as a subsequence of characters. Without a search index,
+    /// associated items are looked for on at most MAX_MEMBER_PAGES type pages.
+    pub fn search(
+        &self,
+        query: &str,
+        crate_name: Option<&str>,
+        kind: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DocItem>> {
+        // Macros are listed without their `!`
+        let query = query.trim().trim_end_matches('!').to_lowercase();
+        let in_scope = |entry: &Entry| {
+            crate_name.is_none_or(|crate_name| {
+                entry.path.split("::").next() == Some(crate_name.replace('-', "_").as_str())
+            }) && kind.is_none_or(|kind| entry.kind == kind)
+        };
+
+        let mut matches = self
+            .entries
+            .iter()
+            .filter(|entry| in_scope(entry))
+            .filter_map(|entry| Some((match_score(&query, entry)?, entry.clone())))
+            .collect::<Vec<_>>();
+
+        // HTML docs have no page per associated item, so without a search index look for them
+        // on their type's page: that of the type named in a `Type::member` query, or of the
+        // first types in scope otherwise
+        if matches.is_empty() && self.source == DocSource::Html {
+            let (parent, member) = match query.rsplit_once("::") {
+                Some((parent, member)) => (Some(parent), member),
+                None => (None, query.as_str()),
+            };
+            let mut pages_read = 0;
+            for entry in &self.entries {
+                if pages_read == MAX_MEMBER_PAGES {
+                    log::info!("stopped looking for {query:?} after {MAX_MEMBER_PAGES} pages");
+                    break;
+                }
+                let Details::Html { page, .. } = &entry.details else {
+                    continue;
+                };
+                let is_parent = match parent {
+                    Some(parent) => match_score(parent, entry) == Some(0),
+                    None => {
+                        HAS_MEMBERS.contains(&entry.kind.as_str())
+                            && crate_name.is_none_or(|crate_name| {
+                                entry.path.split("::").next()
+                                    == Some(crate_name.replace('-', "_").as_str())
+                            })
+                    }
+                };
+                if !is_parent {
+                    continue;
+                }
+                pages_read += 1;
+                let html = read_page(page)?;
+                for (anchor, member_kind, name) in member_anchors(own_members(&html)) {
+                    if kind.is_some_and(|kind| kind != member_kind) {
+                        continue;
+                    }
+                    if let Some(score) = name_score(member, &name.to_lowercase()) {
+                        let member_entry = Entry {
+                            path: format!("{}::{name}", entry.path),
+                            name: name.to_string(),
+                            kind: member_kind.to_string(),
+                            details: Details::Html {
+                                page: page.clone(),
+                                anchor: Some(anchor.to_string()),
+                            },
+                        };
+                        matches.push((score, member_entry));
+                    }
+                }
+            }
+        }
+
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            (a_score, a.path.len(), &a.path).cmp(&(b_score, b.path.len(), &b.path))
+        });
+
+        let mut items: Vec<DocItem> = Vec::new();
+        for (_, entry) in matches {
+            if items.len() >= limit {
+                break;
+            }
+            let item = describe(&entry)?;
+            // rustdoc documents re-exported items again at each path they're exported under
+            let crate_of = |item: &DocItem| item.path.split("::").next().map(String::from);
+            let duplicate = items.iter().any(|other| {
+                other.kind == item.kind
+                    && other.signature == item.signature
+                    && other.docs == item.docs
+                    && crate_of(other) == crate_of(&item)
+            });
+            if !duplicate {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// Whether the search index has a root module for each crate directory
+fn covers_crates(items: &[search_index::IndexedItem], crate_dirs: &[PathBuf]) -> bool {
+    !crate_dirs.is_empty()
+        && crate_dirs.iter().all(|dir| {
+            let page = dir.join("index.html");
+            items
+                .iter()
+                .any(|item| item.kind == "mod" && page.ends_with(&item.page))
+        })
+}
+
+/// Lower is better; `None` means no match
+fn match_score(query: &str, entry: &Entry) -> Option<u32> {
+    if query.contains("::") {
+        let path = entry.path.to_lowercase();
+        if path == query || path.ends_with(&format!("::{query}")) {
+            Some(0)
+        } else if path.contains(query) {
+            Some(2)
+        } else {
+            None
+        }
+    } else {
+        name_score(query, &entry.name.to_lowercase())
+    }
+}
+
+fn name_score(query: &str, name: &str) -> Option<u32> {
+    if name == query {
+        Some(0)
+    } else if name.starts_with(query) {
+        Some(1)
+    } else if name.contains(query) {
+        Some(2)
+    } else {
+        let mut chars = name.chars();
+        query
+            .chars()
+            .all(|wanted| chars.any(|c| c == wanted))
+            .then_some(3)
+    }
+}
+
+fn describe(entry: &Entry) -> Result<DocItem> {
+    match &entry.details {
+        Details::Json(item) => Ok((**item).clone()),
+        Details::Html { page, anchor } => {
+            let html = read_page(page)?;
+            let mut item = match anchor {
+                Some(anchor) => describe_member(&html, anchor),
+                None => describe_page(&html, &entry.kind),
+            };
+            item.path = entry.path.clone();
+            item.kind = entry.kind.clone();
+            item.page = Some(page.clone());
+            Ok(item)
+        }
+    }
+}
+
+//
+// HTML documentation
+//
+
+/// Index a module directory: `struct.Foo.html` and friends, plus submodule directories
+fn index_html_module(dir: &Path, module_path: &str, entries: &mut Vec<Entry>) -> Result<()> {
+    let module_name = module_path.rsplit("::").next().unwrap_or(module_path);
+    entries.push(Entry {
+        path: module_path.to_string(),
+        name: module_name.to_string(),
+        kind: "mod".into(),
+        details: Details::Html {
+            page: dir.join("index.html"),
+            anchor: None,
+        },
+    });
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if path.join("index.html").is_file() {
+                index_html_module(&path, &format!("{module_path}::{file_name}"), entries)?;
+            }
+            continue;
+        }
+
+        let Some((kind, name)) = file_name
+            .strip_suffix(".html")
+            .and_then(|stem| stem.split_once('.'))
+        else {
+            continue;
+        };
+        let kind = match kind {
+            "struct" | "enum" | "union" | "trait" | "fn" | "type" | "constant" | "static"
+            | "macro" | "primitive" | "keyword" => kind,
+            "traitalias" => "trait_alias",
+            "attr" | "derive" => "macro",
+            _ => continue,
+        };
+        let name = name.trim_end_matches('!');
+        entries.push(Entry {
+            path: format!("{module_path}::{name}"),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            details: Details::Html {
+                page: path.clone(),
+                anchor: None,
+            },
+        });
+    }
+    Ok(())
+}
+
+/// Read an item page, following the redirect stubs rustdoc leaves at old paths
+fn read_page(page: &Path) -> Result<String> {
+    let html =
+        fs::read_to_string(page).map_err(|e| anyhow!("Cannot read {}: {e}", page.display()))?;
+    if html.len() < 1000
+        && let Some(target) = html
+            .split_once("http-equiv=\"refresh\" content=\"0;URL=")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(target, _)| target)
+        && let Some(dir) = page.parent()
+    {
+        return fs::read_to_string(dir.join(target))
+            .map_err(|e| anyhow!("Cannot read {}: {e}", dir.join(target).display()));
+    }
+    Ok(html)
+}
+
+fn describe_page(html: &str, kind: &str) -> DocItem {
+    let signature = between(
+        html,
+        "<pre class=\"rust item-decl\"><code>",
+        "</code></pre>",
+    )
+    .map(html_to_text)
+    .filter(|signature| !signature.is_empty());
+
+    let docs = html
+        .split_once("<details class=\"toggle top-doc\"")
+        .and_then(|(_, rest)| inner_div(rest, "<div class=\"docblock\">"))
+        .or_else(|| {
+            // Modules and undocumented-toggle pages put the docblock straight after the heading
+            let heading_end = html.find("</div>")?;
+            let rest = &html[heading_end..];
+            let section = rest.find("<h2").unwrap_or(rest.len());
+            inner_div(&rest[..section], "<div class=\"docblock\">")
+        })
+        .map(html_to_text)
+        .filter(|docs| !docs.is_empty());
+
+    let members = if kind == "mod" {
+        Vec::new()
+    } else {
+        member_anchors(own_members(html))
+            .into_iter()
+            .filter_map(|(anchor, _, _)| describe_member(html, anchor).signature)
+            .collect()
+    };
+
+    DocItem {
+        signature,
+        docs,
+        members,
+        ..DocItem::default()
+    }
+}
+
+/// The part of a page that documents the item's own members, before its trait implementations
+fn own_members(html: &str) -> &str {
+    let end = FOREIGN_IMPL_SECTIONS
+        .iter()
+        .filter_map(|marker| html.find(marker))
+        .min()
+        .unwrap_or(html.len());
+    &html[..end]
+}
+
+/// Associated items and variants on a page: (anchor, kind, name)
+fn member_anchors(html: &str) -> Vec<(&str, &'static str, &str)> {
+    let mut anchors = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<section id=\"") {
+        rest = &rest[start + "<section id=\"".len()..];
+        let Some(end) = rest.find('"') else {
+            break;
+        };
+        let anchor = &rest[..end];
+        let Some((prefix, name)) = anchor.split_once('.') else {
+            continue;
+        };
+        let kind = match prefix {
+            "method" | "tymethod" => "method",
+            "associatedconstant" => "assoc_const",
+            "associatedtype" => "assoc_type",
+            "variant" => "variant",
+            _ => continue,
+        };
+        if !anchors.iter().any(|(other, _, _)| *other == anchor) {
+            anchors.push((anchor, kind, name));
+        }
+    }
+    anchors
+}
+
+fn describe_member(html: &str, anchor: &str) -> DocItem {
+    let Some(start) = html.find(&format!("<section id=\"{anchor}\"")) else {
+        return DocItem::default();
+    };
+    let section = &html[start..];
+    let signature = section
+        .split_once("class=\"code-header\">")
+        .and_then(|(_, rest)| {
+            let end = ["</h3>", "</h4>"]
+                .iter()
+                .filter_map(|tag| rest.find(tag))
+                .min()?;
+            Some(html_to_text(&rest[..end]))
+        });
+
+    let after = section.find("</section>").map_or("", |end| &section[end..]);
+    let next = after[1..]
+        .find("<section")
+        .map_or(after.len(), |next| next + 1);
+    let docs = inner_div(&after[..next], "<div class=\"docblock\">")
+        .map(html_to_text)
+        .filter(|docs| !docs.is_empty());
+
+    DocItem {
+        signature,
+        docs,
+        ..DocItem::default()
+    }
+}
+
+fn between<'a>(html: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = html.split_once(start)?;
+    Some(rest.split_once(end)?.0)
+}
+
+/// The contents of the first div opened by `open`, taking nested divs into account
+fn inner_div<'a>(html: &'a str, open: &str) -> Option<&'a str> {
+    let start = html.find(open)? + open.len();
+    let contents = &html[start..];
+    let mut depth = 1;
+    let mut position = 0;
+    while let Some(offset) = contents[position..].find("div") {
+        let at = position + offset;
+        if contents[..at].ends_with("</") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(&contents[..at - 2]);
+            }
+        } else if contents[..at].ends_with('<') {
+            depth += 1;
+        }
+        position = at + 3;
+    }
+    None
+}
+
+/// Turn rustdoc's HTML into plain text, keeping code blocks fenced and inline code quoted
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_pre = false;
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        // Line breaks between block elements are layout, not text
+        let between = &rest[..open];
+        if in_pre || !between.contains('\n') || !between.trim().is_empty() {
+            text.push_str(between);
+        }
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        let closing = tag.starts_with('/');
+        match (name, closing) {
+            ("pre", false) => {
+                in_pre = true;
+                text.push_str("\n```\n");
+            }
+            ("pre", true) => {
+                in_pre = false;
+                text.push_str("\n```\n\n");
+            }
+            ("code", _) if !in_pre => text.push('`'),
+            ("p" | "ul" | "ol" | "table", true) => text.push_str("\n\n"),
+            ("li", false) => text.push_str("- "),
+            ("li" | "tr" | "br", _) => text.push('\n'),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => text.push_str("## "),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => text.push_str("\n\n"),
+            _ => {}
+        }
+    }
+    text.push_str(rest);
+
+    // Drop heading anchors and the marker rustdoc puts on return types with notable traits
+    let text = unescape(&text).replace(['§', 'ⓘ'], "");
+    let mut result = String::new();
+    let mut blank_lines = 0;
+    for line in text.trim().lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result.trim_end().to_string()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+//
+// rustdoc JSON
+//
+
+/// Add every public item of one crate's rustdoc JSON to the index
+fn index_json(json: &Value, entries: &mut Vec<Entry>) {
+    let (Some(index), Some(paths)) = (json["index"].as_object(), json["paths"].as_object()) else {
+        return;
+    };
+    let item = |id: &Value| index.get(&id_key(id));
+
+    // Inherent methods and trait impls' items belong to the type they're implemented for
+    let mut methods: HashMap<String, Vec<&Value>> = HashMap::new();
+    for impl_item in index.values() {
+        let Some(imp) = impl_item["inner"].get("impl") else {
+            continue;
+        };
+        if !imp["trait"].is_null() || !imp["blanket_impl"].is_null() {
+            continue;
+        }
+        if let Some(type_id) = imp["for"]["resolved_path"].get("id") {
+            methods.entry(id_key(type_id)).or_default().extend(
+                imp["items"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(item),
+            );
+        }
+    }
+
+    for (id, value) in index {
+        if value["crate_id"] != 0 {
+            continue;
+        }
+        let Some(path) = paths.get(id).and_then(|path| path["path"].as_array()) else {
+            continue;
+        };
+        let path = path
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("::");
+        let Some((kind, inner)) = value["inner"]
+            .as_object()
+            .and_then(|inner| inner.iter().next())
+        else {
+            continue;
+        };
+
+        let mut members = Vec::new();
+        let mut children = Vec::new();
+        match kind.as_str() {
+            "struct" | "union" => {
+                let fields = &inner["kind"];
+                let fields = fields["plain"]["fields"]
+                    .as_array()
+                    .or_else(|| fields["tuple"].as_array())
+                    .or_else(|| inner["fields"].as_array());
+                members.extend(
+                    fields
+                        .into_iter()
+                        .flatten()
+                        .filter_map(item)
+                        .map(render_item),
+                );
+            }
+            "enum" => {
+                for variant in inner["variants"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(item)
+                {
+                    members.push(render_item(variant));
+                    children.push(("variant", variant));
+                }
+            }
+            "trait" => {
+                for trait_item in inner["items"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(item)
+                {
+                    members.push(render_item(trait_item));
+                    children.push((json_kind(trait_item, true), trait_item));
+                }
+            }
+            _ => {}
+        }
+        for method in methods.get(id).into_iter().flatten() {
+            members.push(render_item(method));
+            children.push((json_kind(method, true), method));
+        }
+
+        let kind = json_kind(value, false);
+        entries.push(json_entry(&path, kind, value, members));
+        for (kind, child) in children {
+            if let Some(name) = child["name"].as_str() {
+                entries.push(json_entry(
+                    &format!("{path}::{name}"),
+                    kind,
+                    child,
+                    Vec::new(),
+                ));
+            }
+        }
+    }
+}
+
+fn json_entry(path: &str, kind: &str, value: &Value, members: Vec<String>) -> Entry {
+    Entry {
+        path: path.to_string(),
+        name: path.rsplit("::").next().unwrap_or(path).to_string(),
+        kind: kind.to_string(),
+        details: Details::Json(Box::new(DocItem {
+            path: path.to_string(),
+            kind: kind.to_string(),
+            signature: Some(render_item(value)),
+            docs: value["docs"].as_str().map(String::from),
+            members,
+            page: None,
+        })),
+    }
+}
+
+/// Item IDs are numbers in the JSON but strings as keys of `index` and `paths`
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+/// The kind names used for HTML pages, so searches can filter both alike
+fn json_kind(value: &Value, associated: bool) -> &'static str {
+    let kind = value["inner"]
+        .as_object()
+        .and_then(|inner| inner.keys().next())
+        .map_or("", String::as_str);
+    match kind {
+        "function" if associated => "method",
+        "function" => "fn",
+        "module" => "mod",
+        "struct" => "struct",
+        "enum" => "enum",
+        "union" => "union",
+        "trait" => "trait",
+        "trait_alias" => "trait_alias",
+        "type_alias" => "type",
+        "constant" => "constant",
+        "static" => "static",
+        "macro" | "proc_macro" => "macro",
+        "primitive" => "primitive",
+        "variant" => "variant",
+        "assoc_const" => "assoc_const",
+        "assoc_type" => "assoc_type",
+        "struct_field" => "field",
+        _ => "item",
+    }
+}
+
+/// A one-line declaration of an item, as it would appear in source
+fn render_item(value: &Value) -> String {
+    let name = value["name"].as_str().unwrap_or("_");
+    let visibility = if value["visibility"] == "public" {
+        "pub "
+    } else {
+        ""
+    };
+    let Some((kind, inner)) = value["inner"]
+        .as_object()
+        .and_then(|inner| inner.iter().next())
+    else {
+        return name.to_string();
+    };
+
+    match kind.as_str() {
+        "function" => {
+            let header = &inner["header"];
+            let mut qualifiers = String::new();
+            for (flag, keyword) in [
+                ("is_const", "const "),
+                ("is_async", "async "),
+                ("is_unsafe", "unsafe "),
+            ] {
+                if header[flag] == true {
+                    qualifiers.push_str(keyword);
+                }
+            }
+            let inputs = inner["sig"]["inputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|input| render_input(input[0].as_str().unwrap_or("_"), &input[1]))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let output = match &inner["sig"]["output"] {
+                Value::Null => String::new(),
+                output => format!(" -> {}", render_type(output)),
+            };
+            format!(
+                "{visibility}{qualifiers}fn {name}{}({inputs}){output}",
+                render_generics(&inner["generics"])
+            )
+        }
+        "struct" | "union" | "enum" | "trait" => {
+            let keyword = kind.as_str();
+            format!(
+                "{visibility}{keyword} {name}{}",
+                render_generics(&inner["generics"])
+            )
+        }
+        "struct_field" => format!("{visibility}{name}: {}", render_type(inner)),
+        "variant" => {
+            let fields = &inner["kind"];
+            if fields.get("tuple").is_some() {
+                format!("{name}(..)")
+            } else if fields.get("struct").is_some() {
+                format!("{name} {{ .. }}")
+            } else {
+                name.to_string()
+            }
+        }
+        "type_alias" => format!(
+            "{visibility}type {name}{} = {}",
+            render_generics(&inner["generics"]),
+            render_type(&inner["type"])
+        ),
+        "constant" => {
+            let value = inner["const"]["expr"].as_str().unwrap_or("..");
+            format!(
+                "{visibility}const {name}: {} = {value}",
+                render_type(&inner["type"])
+            )
+        }
+        "static" => format!(
+            "{visibility}static {}{name}: {}",
+            if inner["is_mutable"] == true {
+                "mut "
+            } else {
+                ""
+            },
+            render_type(&inner["type"])
+        ),
+        "assoc_const" => format!("const {name}: {}", render_type(&inner["type"])),
+        "assoc_type" => format!("type {name}"),
+        "module" => format!("{visibility}mod {name}"),
+        "macro" => format!("macro_rules! {name}"),
+        _ => name.to_string(),
+    }
+}
+
+fn render_input(name: &str, ty: &Value) -> String {
+    if name == "self" {
+        match ty {
+            ty if ty["generic"] == "Self" => return "self".into(),
+            ty if ty["borrowed_ref"]["type"]["generic"] == "Self" => {
+                let reference = &ty["borrowed_ref"];
+                let mutability = if reference["is_mutable"] == true {
+                    "mut "
+                } else {
+                    ""
+                };
+                let lifetime = reference["lifetime"]
+                    .as_str()
+                    .map(|lifetime| format!("{lifetime} "))
+                    .unwrap_or_default();
+                return format!("&{lifetime}{mutability}self");
+            }
+            _ => {}
+        }
+    }
+    format!("{name}: {}", render_type(ty))
+}
+
+fn render_generics(generics: &Value) -> String {
+    let params = generics["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|param| {
+            let name = param["name"].as_str()?;
+            let kind = &param["kind"];
+            if kind["type"]["is_synthetic"] == true {
+                return None;
+            }
+            let bounds = render_bounds(&kind["type"]["bounds"]);
+            Some(if bounds.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name}: {bounds}")
+            })
+        })
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn render_bounds(bounds: &Value) -> String {
+    bounds
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|bound| {
+            if let Some(lifetime) = bound["outlives"].as_str() {
+                lifetime.to_string()
+            } else {
+                render_path(&bound["trait_bound"]["trait"])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn render_path(path: &Value) -> String {
+    let mut rendered = path["path"].as_str().unwrap_or("_").to_string();
+    let args = &path["args"];
+    if let Some(args) = args["angle_bracketed"]["args"].as_array()
+        && !args.is_empty()
+    {
+        let args = args
+            .iter()
+            .map(|arg| {
+                if let Some(lifetime) = arg["lifetime"].as_str() {
+                    lifetime.to_string()
+                } else if arg.get("type").is_some() {
+                    render_type(&arg["type"])
+                } else {
+                    "_".to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        let _ = write!(rendered, "<{}>", args.join(", "));
+    } else if let Some(parenthesized) = args.get("parenthesized") {
+        let inputs = parenthesized["inputs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(render_type)
+            .collect::<Vec<_>>();
+        let _ = write!(rendered, "({})", inputs.join(", "));
+        if !parenthesized["output"].is_null() {
+            let _ = write!(rendered, " -> {}", render_type(&parenthesized["output"]));
+        }
+    }
+    rendered
+}
+
+/// Render a rustdoc JSON type, falling back to `_` for shapes it doesn't know
+pub fn render_type(ty: &Value) -> String {
+    let Some((kind, inner)) = ty.as_object().and_then(|ty| ty.iter().next()) else {
+        return "_".into();
+    };
+    match kind.as_str() {
+        "resolved_path" => render_path(inner),
+        "generic" | "primitive" => inner.as_str().unwrap_or("_").to_string(),
+        "borrowed_ref" => {
+            let lifetime = inner["lifetime"]
+                .as_str()
+                .map(|lifetime| format!("{lifetime} "))
+                .unwrap_or_default();
+            let mutability = if inner["is_mutable"] == true {
+                "mut "
+            } else {
+                ""
+            };
+            format!("&{lifetime}{mutability}{}", render_type(&inner["type"]))
+        }
+        "raw_pointer" => format!(
+            "*{} {}",
+            if inner["is_mutable"] == true {
+                "mut"
+            } else {
+                "const"
+            },
+            render_type(&inner["type"])
+        ),
+        "slice" => format!("[{}]", render_type(inner)),
+        "array" => format!(
+            "[{}; {}]",
+            render_type(&inner["type"]),
+            inner["len"].as_str().unwrap_or("_")
+        ),
+        "tuple" => {
+            let types = inner
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(render_type)
+                .collect::<Vec<_>>();
+            format!("({})", types.join(", "))
+        }
+        "impl_trait" => format!("impl {}", render_bounds(inner)),
+        "dyn_trait" => {
+            let traits = inner["traits"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|bound| render_path(&bound["trait"]))
+                .collect::<Vec<_>>();
+            format!("dyn {}", traits.join(" + "))
+        }
+        "qualified_path" => {
+            let name = inner["name"].as_str().unwrap_or("_");
+            let self_type = render_type(&inner["self_type"]);
+            if inner["trait"].is_null() {
+                format!("{self_type}::{name}")
+            } else {
+                format!("<{self_type} as {}>::{name}", render_path(&inner["trait"]))
+            }
+        }
+        "function_pointer" => "fn(..)".into(),
+        "infer" => "_".into(),
+        _ => "_".into(),
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The files a `--check -l` or `--check --message-format short` run listed as unformatted
+pub fn listed_files(stdout: &str, project_path: &Path) -> Vec<PathBuf> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.ends_with(".rs"))
+        .map(|line| project_path.join(line))
+        .collect()
+}
+
+/// Split `rustfmt --emit stdout` output, which prints each file after a `path:` line and a
+/// blank line, into the formatted contents of `files`. A module reached both through its parent
+/// and as a file of its own is printed once for each, so a repeated file replaces the earlier copy.
+pub fn split_emitted(stdout: &str, files: &[PathBuf]) -> HashMap<PathBuf, String> {
+    let mut formatted: HashMap<PathBuf, String> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    let mut lines = stdout.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        let header = line
+            .trim_end()
+            .strip_suffix(':')
+            .map(PathBuf::from)
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs") && path.is_file());
+        if let Some(path) = header
+            && lines.peek().is_some_and(|next| next.trim().is_empty())
+        {
+            lines.next();
+            formatted.insert(path.clone(), String::new());
+            current = Some(path);
+            continue;
+        }
+        if let Some(text) = current.as_ref().and_then(|path| formatted.get_mut(path)) {
+            text.push_str(line);
+        }
+    }
+    formatted.retain(|path, _| files.contains(path));
+    formatted
+}
@@ -0,0 +1,481 @@
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Releases of rustdoc (major, minor) whose search index this module decodes. The format is
+/// internal to rustdoc and unversioned: it was taken from rustdoc 1.95, and 1.97 nightly already
+/// writes a different one. Documentation from other releases is indexed from its HTML pages.
+pub const RUSTDOC_VERSIONS: RangeInclusive<(u32, u32)> = (1, 95)..=(1, 95);
+
+/// Item kinds in the order rustdoc numbers them in its search index
+const ITEM_TYPES: [&str; 28] = [
+    "keyword",
+    "primitive",
+    "mod",
+    "externcrate",
+    "import",
+    "struct",
+    "enum",
+    "fn",
+    "type",
+    "static",
+    "trait",
+    "impl",
+    "tymethod",
+    "method",
+    "structfield",
+    "variant",
+    "macro",
+    "associatedtype",
+    "constant",
+    "associatedconstant",
+    "union",
+    "foreigntype",
+    "existential",
+    "attr",
+    "derive",
+    "traitalias",
+    "generic",
+    "attribute",
+];
+
+/// Cookies that start a serialized roaring bitmap, without and with run containers
+const BITMAP_COOKIE: u32 = 12346;
+const BITMAP_COOKIE_RUNS: u16 = 12347;
+
+/// An item listed in the search index `cargo doc` writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedItem {
+    /// Full path as rustdoc displays it, e.g. `serde_json::Value::as_str`
+    pub path: String,
+    pub name: String,
+    /// The kind names used for HTML pages: "struct", "fn", "method", "variant", ...
+    pub kind: &'static str,
+    /// The page documenting the item, relative to the documentation directory
+    pub page: PathBuf,
+    /// The item's anchor on its parent's page, for associated items and variants
+    pub anchor: Option<String>,
+}
+
+/// Read every item in the `search.index` directory of `doc_dir`, or `None` if there is none
+/// (older versions of rustdoc write a `search-index.js` file instead). Fails for an index written
+/// by a rustdoc outside RUSTDOC_VERSIONS, as told by the `help.html` page written with it.
+pub fn load(doc_dir: &Path) -> Result<Option<Vec<IndexedItem>>> {
+    let dir = doc_dir.join("search.index");
+    let Ok(root) = fs::read_to_string(dir.join("root.js")) else {
+        return Ok(None);
+    };
+    let help = fs::read_to_string(doc_dir.join("help.html")).unwrap_or_default();
+    match rustdoc_version(&help) {
+        Some(version) if RUSTDOC_VERSIONS.contains(&version) => {}
+        Some((major, minor)) => {
+            bail!("Search index written by rustdoc {major}.{minor}, whose format is unknown")
+        }
+        None => bail!("Search index written by an unknown version of rustdoc"),
+    }
+    let items = parse(&root, |column, file| {
+        let path = dir.join(column).join(format!("{file}.js"));
+        fs::read_to_string(&path).map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))
+    })?;
+    Ok(Some(items))
+}
+
+/// The release of rustdoc that rendered an HTML page, from its `data-rustdoc-version` attribute
+pub fn rustdoc_version(html: &str) -> Option<(u32, u32)> {
+    let (_, rest) = html.split_once("data-rustdoc-version=\"")?;
+    let mut numbers = rest.split(['.', '-', ' ', '"']);
+    Some((numbers.next()?.parse().ok()?, numbers.next()?.parse().ok()?))
+}
+
+/// Parse the search index from its `root.js`, reading each column's data files (by column and
+/// file name, without `.js`) with `read`
+pub fn parse(root: &str, read: impl Fn(&str, &str) -> Result<String>) -> Result<Vec<IndexedItem>> {
+    // rr_('{"name":{"N":..,"E":..,"H":..},..}')
+    let json = root
+        .split_once("rr_('")
+        .and_then(|(_, rest)| rest.rsplit_once("')"))
+        .map(|(json, _)| js_string(json))
+        .ok_or_else(|| anyhow!("Unrecognised search index root"))?;
+    let root: Value = serde_json::from_slice(&json)?;
+
+    let names = read_column(&root, "name", &read)?;
+    let entries = read_column(&root, "entry", &read)?;
+    let paths = read_column(&root, "path", &read)?;
+
+    // Rows listed in the path column are modules and types that other items belong to:
+    // [kind, module path, ...]
+    let path_data = |row: usize| -> Option<(usize, String)> {
+        let data: Value = serde_json::from_str(text(&paths, row)?).ok()?;
+        Some((
+            data[0].as_u64()? as usize,
+            data[1].as_str().unwrap_or_default().to_string(),
+        ))
+    };
+    let module_path = |row: usize| -> Option<String> {
+        let name = text(&names, row)?;
+        let (_, parent) = path_data(row)?;
+        Some(if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{parent}::{name}")
+        })
+    };
+
+    let mut items = Vec::new();
+    for (row, entry) in entries.iter().enumerate() {
+        let (Some(entry), Some(name)) = (entry, text(&names, row)) else {
+            continue;
+        };
+        // [crate, kind, module path, exact module path, parent, trait, deprecated, unstable],
+        // with rows offset by one so that 0 means none
+        let entry = serde_json::from_str::<Vec<Value>>(entry)
+            .map_err(|e| anyhow!("Unrecognised search index entry {entry:?}: {e}"))?;
+        let field = |index: usize| {
+            entry
+                .get(index)
+                .and_then(Value::as_u64)
+                .map_or(0, |value| value as usize)
+        };
+        // Items of trait implementations are documented with the trait, and left out of a
+        // type's own members
+        if field(5) != 0 {
+            continue;
+        }
+        let Some(&item_type) = ITEM_TYPES.get(field(1)) else {
+            bail!("Unrecognised item kind {} in search index", field(1));
+        };
+        let Some(kind) = page_kind(item_type) else {
+            continue;
+        };
+        let module = match field(2) {
+            0 => None,
+            row => Some(module_path(row - 1).ok_or_else(|| {
+                anyhow!("Search index entry for {name} has an unrecognised module")
+            })?),
+        };
+        let module_dir = module
+            .as_deref()
+            .map(|module| PathBuf::from(module.replace("::", "/")))
+            .unwrap_or_default();
+        let qualified = |name: &str| match &module {
+            Some(module) => format!("{module}::{name}"),
+            None => name.to_string(),
+        };
+
+        let item = match (item_type, field(4)) {
+            ("externcrate", _) => IndexedItem {
+                path: name.to_string(),
+                name: name.to_string(),
+                kind,
+                page: Path::new(name).join("index.html"),
+                anchor: None,
+            },
+            ("mod", _) => IndexedItem {
+                path: qualified(name),
+                name: name.to_string(),
+                kind,
+                page: module_dir.join(name).join("index.html"),
+                anchor: None,
+            },
+            (_, 0) => IndexedItem {
+                path: qualified(name),
+                name: name.to_string(),
+                kind,
+                page: module_dir.join(format!("{item_type}.{name}.html")),
+                anchor: None,
+            },
+            (_, parent) => {
+                let (Some(parent_name), Some((parent_type, _))) =
+                    (text(&names, parent - 1), path_data(parent - 1))
+                else {
+                    bail!("Search index entry for {name} has an unrecognised parent");
+                };
+                let Some(parent_type) = ITEM_TYPES.get(parent_type) else {
+                    bail!("Unrecognised item kind {parent_type} in search index");
+                };
+                IndexedItem {
+                    path: qualified(&format!("{parent_name}::{name}")),
+                    name: name.to_string(),
+                    kind,
+                    page: module_dir.join(format!("{parent_type}.{parent_name}.html")),
+                    anchor: Some(format!("{item_type}.{name}")),
+                }
+            }
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn text(column: &[Option<String>], row: usize) -> Option<&str> {
+    column.get(row)?.as_deref()
+}
+
+/// The kind name used for HTML pages, or `None` for items that don't get a search result
+fn page_kind(item_type: &'static str) -> Option<&'static str> {
+    Some(match item_type {
+        "externcrate" => "mod",
+        "tymethod" => "method",
+        "associatedtype" => "assoc_type",
+        "associatedconstant" => "assoc_const",
+        "traitalias" => "trait_alias",
+        "attr" | "derive" => "macro",
+        "foreigntype" => "type",
+        "import" | "impl" | "structfield" | "existential" | "generic" | "attribute" => {
+            return None;
+        }
+        kind => kind,
+    })
+}
+
+/// One column of the index: a value per row, `None` for rows without one. The values are split
+/// into files named after their hashes, and empty rows are left out of the files and listed in
+/// a bitmap instead.
+fn read_column(
+    root: &Value,
+    name: &str,
+    read: &impl Fn(&str, &str) -> Result<String>,
+) -> Result<Vec<Option<String>>> {
+    let column = &root[name];
+    let field = |key: &str| {
+        column[key]
+            .as_str()
+            .ok_or_else(|| anyhow!("Search index column {name} has no {key}"))
+    };
+    let counts = parse_counts(field("N")?.as_bytes());
+    let hashes = decode_base64(field("H")?)?;
+    let empty = parse_bitmap(&decode_base64(field("E")?)?)?;
+
+    let mut values = Vec::new();
+    for (bucket, count) in counts.iter().enumerate() {
+        let Some(hash) = hashes.get(bucket * 6..(bucket + 1) * 6) else {
+            bail!("Search index column {name} is missing a file hash");
+        };
+        let file = hash
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let data = data_file(&read(name, &file)?)?;
+        let bucket_values = parse_values(&data);
+        if bucket_values.len() != *count {
+            bail!(
+                "Search index file {name}/{file}.js has {} values instead of {count}",
+                bucket_values.len()
+            );
+        }
+        values.extend(bucket_values);
+    }
+
+    let mut rows = Vec::with_capacity(values.len() + empty.len());
+    let mut values = values.into_iter();
+    for row in 0.. {
+        if empty.contains(&row) {
+            rows.push(None);
+        } else if let Some(value) = values.next() {
+            rows.push(Some(String::from_utf8_lossy(&value).into_owned()));
+        } else {
+            break;
+        }
+    }
+    Ok(rows)
+}
+
+/// Bucket sizes, each a number in hexadecimal digits whose last digit is written as a
+/// lowercase character
+fn parse_counts(encoded: &[u8]) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut count = 0;
+    for &byte in encoded {
+        count = (count << 4) | usize::from(byte & 0xF);
+        if byte >= 96 {
+            counts.push(count);
+            count = 0;
+        }
+    }
+    counts
+}
+
+/// The values in a data file: each is its length (encoded like the bucket sizes) followed by
+/// its bytes, or a single byte from `0` to `?` repeating one of the last 16 distinct values
+fn parse_values(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut values = Vec::new();
+    let mut recent: Vec<Vec<u8>> = Vec::new();
+    let mut position = 0;
+    while let Some(&byte) = data.get(position) {
+        if (48..=63).contains(&byte) {
+            values.extend(recent.get(usize::from(byte - 48)).cloned());
+            position += 1;
+            continue;
+        }
+
+        let mut length = 0;
+        while let Some(&byte) = data.get(position) {
+            length = (length << 4) | usize::from(byte & 0xF);
+            position += 1;
+            if byte >= 96 {
+                break;
+            }
+        }
+        let end = (position + length).min(data.len());
+        let value = data[position..end].to_vec();
+        position = end;
+        recent.insert(0, value.clone());
+        recent.truncate(16);
+        values.push(value);
+    }
+    values
+}
+
+/// The bytes of a data file, either `rd_("escaped bytes")` or `rb_("base64")`
+fn data_file(contents: &str) -> Result<Vec<u8>> {
+    let (kind, rest) = contents
+        .trim()
+        .split_once("(\"")
+        .ok_or_else(|| anyhow!("Unrecognised search index file"))?;
+    let body = rest
+        .strip_suffix("\")")
+        .ok_or_else(|| anyhow!("Unrecognised search index file"))?;
+    match kind {
+        "rd_" => Ok(js_string(body)),
+        "rb_" => decode_base64(body),
+        _ => bail!("Unrecognised search index file"),
+    }
+}
+
+/// The bytes of a JavaScript string literal's contents, whose characters each stand for a byte
+fn js_string(literal: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(literal.len());
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            // Characters beyond a byte only appear in the root's JSON, as UTF-8
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some(escape @ ('x' | 'u')) => {
+                let digits = chars
+                    .by_ref()
+                    .take(if escape == 'x' { 2 } else { 4 })
+                    .collect::<String>();
+                if let Ok(code) = u32::from_str_radix(&digits, 16) {
+                    match u8::try_from(code) {
+                        Ok(byte) => bytes.push(byte),
+                        Err(_) => {
+                            let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            let mut buffer = [0; 4];
+                            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                        }
+                    }
+                }
+            }
+            Some(other) => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+            }
+            None => {}
+        }
+    }
+    bytes
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes().filter(|c| *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => bail!("Invalid base64 in search index"),
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// The members of a roaring bitmap in its portable serialization
+fn parse_bitmap(bytes: &[u8]) -> Result<HashSet<usize>> {
+    let mut members = HashSet::new();
+    if bytes.is_empty() {
+        return Ok(members);
+    }
+    let truncated = || anyhow!("Truncated bitmap in search index");
+    let u16_at = |at: usize| -> Result<usize> {
+        let pair = bytes.get(at..at + 2).ok_or_else(truncated)?;
+        Ok(usize::from(u16::from_le_bytes([pair[0], pair[1]])))
+    };
+    let u32_at = |at: usize| -> Result<u32> {
+        let quad = bytes.get(at..at + 4).ok_or_else(truncated)?;
+        Ok(u32::from_le_bytes([quad[0], quad[1], quad[2], quad[3]]))
+    };
+
+    let cookie = u32_at(0)?;
+    let (size, run_flags, mut position) = if cookie & 0xFFFF == u32::from(BITMAP_COOKIE_RUNS) {
+        let size = (cookie >> 16) as usize + 1;
+        let flags = bytes.get(4..4 + size.div_ceil(8)).ok_or_else(truncated)?;
+        (size, flags, 4 + size.div_ceil(8))
+    } else if cookie == BITMAP_COOKIE {
+        (u32_at(4)? as usize, &[][..], 8)
+    } else {
+        bail!("Unrecognised bitmap in search index");
+    };
+
+    let headers = position;
+    position += size * 4;
+    // Offsets to each container, which aren't needed when reading them in order
+    if run_flags.is_empty() || size >= 4 {
+        position += size * 4;
+    }
+
+    for container in 0..size {
+        let key = u16_at(headers + container * 4)? << 16;
+        let cardinality = u16_at(headers + container * 4 + 2)? + 1;
+        let is_run = run_flags
+            .get(container / 8)
+            .is_some_and(|flags| flags & (1 << (container % 8)) != 0);
+        if is_run {
+            let runs = u16_at(position)?;
+            position += 2;
+            for _ in 0..runs {
+                let (start, length) = (u16_at(position)?, u16_at(position + 2)?);
+                members.extend((start..=start + length).map(|value| key | value));
+                position += 4;
+            }
+        } else if cardinality >= 4096 {
+            let words = bytes.get(position..position + 8192).ok_or_else(truncated)?;
+            for (index, byte) in words.iter().enumerate() {
+                members.extend(
+                    (0..8)
+                        .filter(|bit| byte & (1 << bit) != 0)
+                        .map(|bit| key | (index * 8 + bit)),
+                );
+            }
+            position += 8192;
+        } else {
+            for index in 0..cardinality {
+                members.insert(key | u16_at(position + index * 2)?);
+            }
+            position += cardinality * 2;
+        }
+    }
+    Ok(members)
+}
@@ -0,0 +1,46 @@
+use crate::project::describe_project;
+use crate::state::CargoTools;
+use anyhow::Result;
+use mcplease::{
+    traits::{Tool, WithExamples},
+    types::Example,
+};
+use serde::{Deserialize, Serialize};
+
+/// Select the Rust project that subsequent cargo commands operate on
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema, clap::Args)]
+#[serde(rename = "set_working_directory")]
+pub struct SetWorkingDirectory {
+    /// Path to the project, or any directory or file inside it (supports `~`). The nearest
+    /// directory containing a Cargo.toml is selected.
+    pub path: String,
+}
+
+impl WithExamples for SetWorkingDirectory {
+    fn examples() -> Vec<Example<Self>> {
+        vec![
+            Example {
+                description: "Select a project by its root directory",
+                item: Self {
+                    path: "~/code/my-project".into(),
+                },
+            },
+            Example {
+                description: "Select the package containing a source directory",
+                item: Self {
+                    path: "/home/user/code/my-workspace/crates/my-lib/src".into(),
+                },
+            },
+        ]
+    }
+}
+
+impl Tool<CargoTools> for SetWorkingDirectory {
+    fn execute(self, state: &mut CargoTools) -> Result<String> {
+        let package_root = state.set_working_directory(&self.path)?;
+        Ok(format!(
+            "✅ Project selected\n\n{}",
+            describe_project(&package_root)
+        ))
+    }
+}
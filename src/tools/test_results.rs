@@ -0,0 +1,407 @@
+use crate::tools::cargo_utils::{CargoOutput, push_section};
+use crate::tools::diagnostics::{parse_messages, summarize};
+use serde::Serialize;
+use std::{fmt::Write, path::Path};
+
+/// Results reported by a single test binary (unit tests, an integration test or doc-tests)
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestBinary {
+    /// Name as reported by cargo, e.g. "unittests src/lib.rs" or "tests/cli.rs"
+    pub name: String,
+    pub tests: Vec<TestCase>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<TestSummary>,
+}
+
+/// The outcome of a single test
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub outcome: TestOutcome,
+    /// Reason given in `#[ignore = "..."]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panic: Option<Panic>,
+    /// Output captured by the test harness, excluding the panic message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// How long the test took, e.g. "0.012s", when libtest was run with `--report-time`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// The panic that failed a test
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Panic {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// The `test result:` line printed at the end of each test binary
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub measured: usize,
+    pub filtered_out: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+}
+
+/// How many of the slowest tests to list when per-test durations are known
+const SLOWEST_TESTS: usize = 5;
+
+/// Names of the test binaries in the order cargo ran them, taken from the "Running" and
+/// "Doc-tests" lines cargo writes to stderr
+fn binary_names(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if let Some(running) = line.strip_prefix("Running ") {
+                // "unittests src/lib.rs (target/debug/deps/foo-0123abcd)"
+                Some(match running.rfind(" (") {
+                    Some(index) => running[..index].to_string(),
+                    None => running.to_string(),
+                })
+            } else {
+                line.strip_prefix("Doc-tests ")
+                    .map(|krate| format!("doc-tests {krate}"))
+            }
+        })
+        .collect()
+}
+
+fn parse_test_line(line: &str) -> Option<TestCase> {
+    let (name, result) = line.strip_prefix("test ")?.split_once(" ... ")?;
+    // With `--report-time` the outcome is followed by the test's duration: "ok <0.012s>"
+    let (result, duration) = match result
+        .trim_end()
+        .strip_suffix('>')
+        .and_then(|rest| rest.rsplit_once(" <"))
+    {
+        Some((result, duration)) => (result, Some(duration.to_string())),
+        None => (result.trim_end(), None),
+    };
+    let (outcome, ignore_reason) = match result {
+        "ok" => (TestOutcome::Passed, None),
+        "FAILED" => (TestOutcome::Failed, None),
+        "ignored" => (TestOutcome::Ignored, None),
+        other => match other.strip_prefix("ignored, ") {
+            Some(reason) => (TestOutcome::Ignored, Some(reason.to_string())),
+            None => return None,
+        },
+    };
+
+    Some(TestCase {
+        name: name.to_string(),
+        outcome,
+        ignore_reason,
+        panic: None,
+        output: None,
+        duration,
+    })
+}
+
+fn parse_summary(line: &str) -> Option<TestSummary> {
+    let (_, counts) = line.strip_prefix("test result: ")?.split_once(". ")?;
+    let mut summary = TestSummary::default();
+    for part in counts.split("; ") {
+        if let Some(duration) = part.strip_prefix("finished in ") {
+            summary.duration = Some(duration.trim().to_string());
+            continue;
+        }
+
+        let Some((count, label)) = part.split_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse() else {
+            continue;
+        };
+        match label {
+            "passed" => summary.passed = count,
+            "failed" => summary.failed = count,
+            "ignored" => summary.ignored = count,
+            "measured" => summary.measured = count,
+            "filtered out" => summary.filtered_out = count,
+            _ => {}
+        }
+    }
+    Some(summary)
+}
+
+/// Separate the panic message and location from the rest of a failed test's captured output
+fn split_panic(captured: &str) -> (Option<Panic>, String) {
+    let mut panic: Option<Panic> = None;
+    let mut output = String::new();
+    let mut in_message = false;
+
+    for line in captured.lines() {
+        if panic.is_none()
+            && line.starts_with("thread '")
+            && let Some((_, rest)) = line.split_once(" panicked at ")
+        {
+            panic = Some(match rest.strip_suffix(':') {
+                // Current format: location on this line, message on the following lines
+                Some(location) => {
+                    in_message = true;
+                    Panic {
+                        message: String::new(),
+                        location: Some(location.to_string()),
+                    }
+                }
+                // Older format: "panicked at 'message', src/lib.rs:10:5"
+                None => match rest.rsplit_once(", ") {
+                    Some((message, location)) => Panic {
+                        message: message.trim_matches('\'').to_string(),
+                        location: Some(location.to_string()),
+                    },
+                    None => Panic {
+                        message: rest.to_string(),
+                        location: None,
+                    },
+                },
+            });
+            continue;
+        }
+
+        match &mut panic {
+            None => {
+                output.push_str(line);
+                output.push('\n');
+            }
+            // The message runs until the backtrace or the RUST_BACKTRACE hint, both of which
+            // are dropped
+            Some(panic) if in_message => {
+                if line.is_empty() || line.starts_with("note: ") || line == "stack backtrace:" {
+                    in_message = false;
+                } else {
+                    if !panic.message.is_empty() {
+                        panic.message.push('\n');
+                    }
+                    panic.message.push_str(line);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    (panic, output.trim().to_string())
+}
+
+/// Parse libtest's human-readable output into per-binary, per-test results
+pub fn parse_test_output(stdout: &str, stderr: &str) -> Vec<TestBinary> {
+    let mut names = binary_names(stderr).into_iter();
+    let mut binaries: Vec<TestBinary> = Vec::new();
+    let mut current: Option<TestBinary> = None;
+    let mut capturing: Option<(String, String)> = None;
+
+    let finish_capture = |binary: &mut TestBinary, capture: Option<(String, String)>| {
+        if let Some((name, captured)) = capture
+            && let Some(test) = binary.tests.iter_mut().find(|test| test.name == name)
+        {
+            let (panic, output) = split_panic(&captured);
+            test.panic = panic;
+            test.output = (!output.is_empty()).then_some(output);
+        }
+    };
+
+    for line in stdout.lines() {
+        if line.starts_with("running ") && (line.ends_with(" test") || line.ends_with(" tests")) {
+            if let Some(binary) = current.take() {
+                binaries.push(binary);
+            }
+            let index = binaries.len() + 1;
+            current = Some(TestBinary {
+                name: names
+                    .next()
+                    .unwrap_or_else(|| format!("test binary {index}")),
+                tests: Vec::new(),
+                summary: None,
+            });
+            continue;
+        }
+
+        let Some(binary) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            finish_capture(binary, capturing.take());
+            capturing = Some((name.to_string(), String::new()));
+        } else if line == "failures:" {
+            // The second "failures:" heading starts the list of failed test names
+            finish_capture(binary, capturing.take());
+        } else if let Some((_, captured)) = &mut capturing {
+            captured.push_str(line);
+            captured.push('\n');
+        } else if let Some(summary) = parse_summary(line) {
+            binary.summary = Some(summary);
+            binaries.extend(current.take());
+        } else if let Some(test) = parse_test_line(line) {
+            binary.tests.push(test);
+        }
+    }
+
+    if let Some(mut binary) = current.take() {
+        finish_capture(&mut binary, capturing.take());
+        binaries.push(binary);
+    }
+
+    binaries
+}
+
+fn render_failure(out: &mut String, binary: &TestBinary, test: &TestCase) {
+    let _ = write!(out, "  ✗ {} ({})", test.name, binary.name);
+    if let Some(duration) = &test.duration {
+        let _ = write!(out, " in {duration}");
+    }
+    out.push('\n');
+    if let Some(panic) = &test.panic {
+        if let Some(location) = &panic.location {
+            let _ = writeln!(out, "    panicked at {location}");
+        }
+        for line in panic.message.lines() {
+            let _ = writeln!(out, "    {line}");
+        }
+    }
+    if let Some(output) = &test.output {
+        let _ = writeln!(out, "    captured output:");
+        for line in output.lines() {
+            let _ = writeln!(out, "      {line}");
+        }
+    }
+}
+
+/// Format a finished `cargo test --message-format=json` run as a test report: failures first,
+/// then per-binary counts, then the structured results
+pub fn format_test_output(output: &CargoOutput, command_name: &str, project_path: &Path) -> String {
+    let (diagnostics, test_stdout) = parse_messages(&output.stdout);
+    let binaries = parse_test_output(&test_stdout, &output.stderr);
+
+    let mut result = output.header(command_name, project_path);
+
+    if !diagnostics.is_empty() {
+        result.push_str(&format!(
+            "📋 Compiler diagnostics: {}\n",
+            summarize(&diagnostics)
+        ));
+        result.push_str(&serde_json::to_string(&diagnostics).unwrap_or_default());
+        result.push_str("\n\n");
+    }
+
+    let count = |outcome| {
+        binaries
+            .iter()
+            .flat_map(|binary| &binary.tests)
+            .filter(|test| test.outcome == outcome)
+            .count()
+    };
+    let _ = writeln!(
+        result,
+        "🧪 Tests: {} passed, {} failed, {} ignored ({} test binar{})\n",
+        count(TestOutcome::Passed),
+        count(TestOutcome::Failed),
+        count(TestOutcome::Ignored),
+        binaries.len(),
+        if binaries.len() == 1 { "y" } else { "ies" }
+    );
+
+    let failures = binaries.iter().flat_map(|binary| {
+        binary
+            .tests
+            .iter()
+            .filter(|test| test.outcome == TestOutcome::Failed)
+            .map(move |test| (binary, test))
+    });
+    let mut any_failures = false;
+    for (binary, test) in failures {
+        if !any_failures {
+            result.push_str("❌ Failed tests:\n");
+            any_failures = true;
+        }
+        render_failure(&mut result, binary, test);
+    }
+    if any_failures {
+        result.push('\n');
+    }
+
+    // Per-test durations are only known when libtest reported them
+    let mut timed = binaries
+        .iter()
+        .flat_map(|binary| binary.tests.iter().map(move |test| (binary, test)))
+        .filter_map(|(binary, test)| {
+            let seconds = test
+                .duration
+                .as_deref()?
+                .strip_suffix('s')?
+                .parse::<f64>()
+                .ok()?;
+            Some((seconds, binary, test))
+        })
+        .collect::<Vec<_>>();
+    if !timed.is_empty() {
+        timed.sort_by(|a, b| b.0.total_cmp(&a.0));
+        result.push_str("🐢 Slowest tests:\n");
+        for (_, binary, test) in timed.iter().take(SLOWEST_TESTS) {
+            let _ = writeln!(
+                result,
+                "  {} ({}): {}",
+                test.name,
+                binary.name,
+                test.duration.as_deref().unwrap_or_default()
+            );
+        }
+        result.push('\n');
+    }
+
+    if !binaries.is_empty() {
+        result.push_str("📦 Test binaries:\n");
+        for binary in &binaries {
+            let _ = write!(result, "  {}: ", binary.name);
+            match &binary.summary {
+                Some(summary) => {
+                    let _ = write!(
+                        result,
+                        "{} passed, {} failed, {} ignored",
+                        summary.passed, summary.failed, summary.ignored
+                    );
+                    if summary.filtered_out > 0 {
+                        let _ = write!(result, ", {} filtered out", summary.filtered_out);
+                    }
+                    if let Some(duration) = &summary.duration {
+                        let _ = write!(result, " in {duration}");
+                    }
+                    result.push('\n');
+                }
+                None => result.push_str("did not finish\n"),
+            }
+        }
+        result.push('\n');
+
+        result.push_str("🧾 Structured results (JSON):\n");
+        result.push_str(&serde_json::to_string(&binaries).unwrap_or_default());
+        result.push_str("\n\n");
+    } else {
+        // Nothing recognisable as libtest output, so show it as-is
+        push_section(&mut result, "📤 STDOUT:", &test_stdout);
+    }
+
+    push_section(&mut result, "📤 STDERR:", &output.stderr);
+
+    result
+}
@@ -0,0 +1,553 @@
+use anyhow::{Result, anyhow, bail};
+use semver::{Version, VersionReq};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// Dependency tables in a manifest, at the top level or under `[target.'cfg(..)']`
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Keys that say where a dependency comes from, in the order they are written
+const SOURCE_KEYS: [&str; 7] = ["git", "branch", "tag", "rev", "path", "registry", "package"];
+
+/// Keys a member can keep on a dependency it inherits from `[workspace.dependencies]`
+const INHERITABLE_KEYS: [&str; 4] = ["workspace", "features", "optional", "public"];
+
+/// Keys the workspace entry takes over from a member's declaration
+const ENTRY_KEYS: [&str; 3] = ["version", "default-features", "default_features"];
+
+/// A Cargo.toml being edited, keeping its formatting and comments
+#[derive(Debug)]
+pub struct Manifest {
+    pub path: PathBuf,
+    /// The workspace member the manifest belongs to. `None` for the root manifest of a virtual
+    /// workspace.
+    pub member: Option<String>,
+    pub original: String,
+    pub document: DocumentMut,
+}
+
+/// How a dependency is declared, in a member or in `[workspace.dependencies]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependencySpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The git, branch, tag, rev, path, registry and package keys
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub source: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_features: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Whether the dependency is inherited with `workspace = true`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub workspace: bool,
+}
+
+/// A dependency declared in a member's manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct Declaration {
+    pub member: String,
+    /// e.g. `dependencies` or `target.cfg(unix).dev-dependencies`
+    pub table: String,
+    pub key: String,
+    #[serde(flatten)]
+    pub spec: DependencySpec,
+    /// Index of the manifest in the list it was read from
+    #[serde(skip)]
+    pub manifest: usize,
+    /// Keys leading to the dependency table
+    #[serde(skip)]
+    pub table_path: Vec<String>,
+}
+
+/// A dependency moved to `[workspace.dependencies]`
+#[derive(Debug, Serialize)]
+pub struct Promotion {
+    pub name: String,
+    /// The workspace entry the members now inherit
+    pub entry: DependencySpec,
+    /// Whether the workspace table already had the entry
+    pub existing: bool,
+    /// The declarations that now inherit the entry
+    pub declarations: Vec<Declaration>,
+    /// Behavior the rewrite couldn't preserve
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl Manifest {
+    pub fn load(path: PathBuf, member: Option<String>) -> Result<Self> {
+        let original = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Cannot read {}: {e}", path.display()))?;
+        Self::parse(path, member, original)
+    }
+
+    pub fn parse(path: PathBuf, member: Option<String>, original: String) -> Result<Self> {
+        let document = original
+            .parse::<DocumentMut>()
+            .map_err(|e| anyhow!("Cannot parse {}: {e}", path.display()))?;
+        Ok(Self {
+            path,
+            member,
+            original,
+            document,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    pub fn changed(&self) -> bool {
+        self.document.to_string() != self.original
+    }
+
+    /// The dependencies the manifest declares, if it belongs to a member
+    fn declarations(&self, index: usize) -> Vec<Declaration> {
+        let Some(member) = &self.member else {
+            return Vec::new();
+        };
+        let root = self.document.as_table();
+        let mut tables = DEPENDENCY_TABLES
+            .iter()
+            .map(|table| vec![table.to_string()])
+            .collect::<Vec<_>>();
+        if let Some(targets) = root.get("target").and_then(Item::as_table_like) {
+            for (target, _) in targets.iter() {
+                for table in DEPENDENCY_TABLES {
+                    tables.push(vec!["target".into(), target.into(), table.into()]);
+                }
+            }
+        }
+
+        let mut declarations = Vec::new();
+        for table_path in tables {
+            let Some(table) = table_at(root, &table_path) else {
+                continue;
+            };
+            for (key, item) in table.iter() {
+                declarations.push(Declaration {
+                    member: member.clone(),
+                    table: table_path.join("."),
+                    key: key.to_string(),
+                    spec: DependencySpec::read(item),
+                    manifest: index,
+                    table_path: table_path.clone(),
+                });
+            }
+        }
+        declarations
+    }
+}
+
+impl DependencySpec {
+    fn read(item: &Item) -> Self {
+        if let Some(version) = item.as_str() {
+            return Self {
+                version: Some(version.to_string()),
+                ..Self::default()
+            };
+        }
+        let Some(table) = item.as_table_like() else {
+            return Self::default();
+        };
+        let string = |key: &str| table.get(key).and_then(Item::as_str).map(str::to_string);
+        let boolean = |key: &str| table.get(key).and_then(Item::as_bool);
+        Self {
+            version: string("version"),
+            source: SOURCE_KEYS
+                .iter()
+                .filter_map(|key| Some((key.to_string(), string(key)?)))
+                .collect(),
+            default_features: boolean("default-features").or_else(|| boolean("default_features")),
+            features: table
+                .get("features")
+                .and_then(Item::as_array)
+                .map(|features| {
+                    features
+                        .iter()
+                        .filter_map(|feature| feature.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            optional: boolean("optional").unwrap_or(false),
+            workspace: boolean("workspace").unwrap_or(false),
+        }
+    }
+
+    /// The entry as written in `[workspace.dependencies]`: a plain version string when that's
+    /// all there is, otherwise an inline table
+    fn to_value(&self) -> Value {
+        if let Some(version) = &self.version
+            && self.source.is_empty()
+            && self.default_features.is_none()
+            && self.features.is_empty()
+        {
+            return Value::from(version.as_str());
+        }
+        let mut table = InlineTable::new();
+        if let Some(version) = &self.version {
+            table.insert("version", Value::from(version.as_str()));
+        }
+        for key in SOURCE_KEYS {
+            if let Some(value) = self.source.get(key) {
+                table.insert(key, Value::from(value.as_str()));
+            }
+        }
+        if let Some(default_features) = self.default_features {
+            table.insert("default-features", Value::from(default_features));
+        }
+        if !self.features.is_empty() {
+            table.insert("features", Value::Array(self.features.iter().collect()));
+        }
+        Value::InlineTable(table)
+    }
+
+    /// A short description, e.g. `"1.0"` or `{ git = "…", branch = "main" }`
+    pub fn describe(&self) -> String {
+        self.to_value().to_string().trim().to_string()
+    }
+}
+
+fn table_at<'a>(root: &'a Table, path: &[String]) -> Option<&'a dyn TableLike> {
+    let mut table: &dyn TableLike = root;
+    for key in path {
+        table = table.get(key)?.as_table_like()?;
+    }
+    Some(table)
+}
+
+fn table_at_mut<'a>(root: &'a mut Table, path: &[String]) -> Option<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = root;
+    for key in path {
+        table = table.get_mut(key)?.as_table_like_mut()?;
+    }
+    Some(table)
+}
+
+/// Every dependency declared by the members, in manifest order
+pub fn declarations(manifests: &[Manifest]) -> Vec<Declaration> {
+    manifests
+        .iter()
+        .enumerate()
+        .flat_map(|(index, manifest)| manifest.declarations(index))
+        .collect()
+}
+
+/// The entries of the root manifest's `[workspace.dependencies]`
+pub fn workspace_dependencies(root: &Manifest) -> BTreeMap<String, DependencySpec> {
+    table_at(
+        root.document.as_table(),
+        &["workspace".into(), "dependencies".into()],
+    )
+    .map(|table| {
+        table
+            .iter()
+            .map(|(key, item)| (key.to_string(), DependencySpec::read(item)))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// The smallest version a requirement allows, for picking the highest of several requirements
+fn lower_bound(requirement: &str) -> Version {
+    VersionReq::parse(requirement)
+        .ok()
+        .and_then(|requirement| {
+            let comparator = requirement.comparators.first()?;
+            Some(Version::new(
+                comparator.major,
+                comparator.minor.unwrap_or(0),
+                comparator.patch.unwrap_or(0),
+            ))
+        })
+        .unwrap_or(Version::new(0, 0, 0))
+}
+
+/// Rewrite a dependency declared as a table, inline or not, to inherit it from the workspace:
+/// `workspace = true` first, then the keys the member keeps, with their formatting and comments
+fn inherit(declared: &mut dyn TableLike, default_features: Option<bool>) {
+    let kept = declared
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| key != "workspace" && INHERITABLE_KEYS.contains(&key.as_str()))
+        .collect::<Vec<_>>();
+    let kept = kept
+        .iter()
+        .filter_map(|key| declared.get_key_value(key))
+        .map(|(key, item)| (key.clone(), item.clone()))
+        .collect::<Vec<_>>();
+
+    declared.clear();
+    declared.insert("workspace", toml_edit::value(true));
+    for (key, item) in kept {
+        declared.entry_format(&key).or_insert(item);
+    }
+    if let Some(default_features) = default_features {
+        declared.insert("default-features", toml_edit::value(default_features));
+    }
+}
+
+/// Change the version of a dependency declared as a version string or a table, keeping the
+/// formatting around it
+fn set_version(item: &mut Item, version: &str) {
+    let value = match item.as_table_like_mut() {
+        Some(table) if !table.contains_key("version") => {
+            table.insert("version", toml_edit::value(version));
+            return;
+        }
+        Some(table) => table.get_mut("version").and_then(Item::as_value_mut),
+        None => item.as_value_mut(),
+    };
+    if let Some(value) = value {
+        let decor = value.decor().clone();
+        *value = Value::from(version);
+        *value.decor_mut() = decor;
+    }
+}
+
+/// Whether two version requirements overlap, meaning each is met by the other's lowest version
+/// or the other way round. Requirements that don't parse are left for cargo to judge.
+fn compatible(a: &str, b: &str) -> bool {
+    match (VersionReq::parse(a), VersionReq::parse(b)) {
+        (Ok(req_a), Ok(req_b)) => req_a.matches(&lower_bound(b)) || req_b.matches(&lower_bound(a)),
+        _ => true,
+    }
+}
+
+/// The components of `path` with `.` and `..` resolved lexically
+fn normalize(path: &Path) -> Vec<Component<'_>> {
+    let mut parts: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(parts.last(), Some(Component::Normal(_))) => {
+                parts.pop();
+            }
+            component => parts.push(component),
+        }
+    }
+    parts
+}
+
+/// `path` relative to `base`, with `/` separators, both taken lexically
+fn relative_path(path: &Path, base: &Path) -> String {
+    let (path, base) = (normalize(path), normalize(base));
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut relative = vec![".."; base.len() - common];
+    relative.extend(
+        path[common..]
+            .iter()
+            .filter_map(|part| part.as_os_str().to_str()),
+    );
+    if relative.is_empty() {
+        ".".into()
+    } else {
+        relative.join("/")
+    }
+}
+
+/// Move dependency `name` to `[workspace.dependencies]` in the manifest at `root`, and make
+/// every member that declares it inherit it with `workspace = true`. The workspace entry uses
+/// `version` if given, then any existing entry, then the highest version the members ask for.
+/// Fails, changing nothing, if a member asks for a version that entry is incompatible with.
+pub fn promote(
+    manifests: &mut [Manifest],
+    root: usize,
+    name: &str,
+    version: Option<&str>,
+) -> Result<Promotion> {
+    let existing = workspace_dependencies(&manifests[root]).remove(name);
+    let declarations = declarations(manifests)
+        .into_iter()
+        .filter(|declaration| declaration.key == name && !declaration.spec.workspace)
+        .collect::<Vec<_>>();
+    if declarations.is_empty() && existing.is_none() {
+        bail!("No member declares {name}");
+    }
+
+    // Paths are relative to the manifest, so compare them relative to the workspace root
+    let root_dir = manifests[root].dir().to_path_buf();
+    let declared_sources = declarations
+        .iter()
+        .map(|declaration| {
+            let mut source = declaration.spec.source.clone();
+            if let Some(path) = source.get_mut("path") {
+                let absolute = manifests[declaration.manifest].dir().join(&*path);
+                *path = relative_path(&absolute, &root_dir);
+            }
+            source
+        })
+        .collect::<Vec<_>>();
+
+    let sources = declared_sources
+        .iter()
+        .chain(existing.as_ref().map(|entry| &entry.source))
+        .collect::<BTreeSet<_>>();
+    if sources.len() > 1 {
+        bail!(
+            "{name} comes from different sources: {}",
+            sources
+                .iter()
+                .map(|source| if source.is_empty() {
+                    "the registry".to_string()
+                } else {
+                    source
+                        .iter()
+                        .map(|(key, value)| format!("{key} = {value:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
+
+    let mut entry = existing.clone().unwrap_or_else(|| DependencySpec {
+        version: declarations
+            .iter()
+            .filter_map(|declaration| declaration.spec.version.as_deref())
+            .max_by_key(|version| lower_bound(version))
+            .map(str::to_string),
+        source: declared_sources.first().cloned().unwrap_or_default(),
+        default_features: declarations
+            .iter()
+            .any(|declaration| declaration.spec.default_features == Some(false))
+            .then_some(false),
+        ..DependencySpec::default()
+    });
+    if let Some(version) = version {
+        entry.version = Some(version.to_string());
+    }
+    if entry.version.is_none() && entry.source.is_empty() {
+        bail!("No version for {name}: pass one to use in the workspace");
+    }
+    // Inheriting a version from another semver series would change which release members get
+    if let Some(version) = &entry.version {
+        let incompatible = declarations
+            .iter()
+            .filter_map(|declaration| {
+                let requirement = declaration.spec.version.as_deref()?;
+                (!compatible(requirement, version))
+                    .then(|| format!("{} ({requirement})", declaration.member))
+            })
+            .collect::<Vec<_>>();
+        if !incompatible.is_empty() {
+            bail!(
+                "{name} {version} is incompatible with {}",
+                incompatible.join(", ")
+            );
+        }
+    }
+
+    // Anything else a member sets can't be expressed once it inherits the dependency
+    for declaration in &declarations {
+        let unsupported = table_at(
+            manifests[declaration.manifest].document.as_table(),
+            &declaration.table_path,
+        )
+        .and_then(|table| table.get(name))
+        .and_then(Item::as_table_like)
+        .map(|declared| {
+            declared
+                .iter()
+                .map(|(key, _)| key)
+                .filter(|key| {
+                    !INHERITABLE_KEYS.contains(key)
+                        && !ENTRY_KEYS.contains(key)
+                        && !SOURCE_KEYS.contains(key)
+                })
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+        if !unsupported.is_empty() {
+            bail!(
+                "{} sets {} on {name}, which an inherited dependency can't",
+                declaration.member,
+                unsupported.join(", ")
+            );
+        }
+    }
+
+    let root_table = manifests[root].document.as_table_mut();
+    let workspace = root_table
+        .get_mut("workspace")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| anyhow!("The root manifest has no [workspace] table"))?;
+    if workspace.get("dependencies").is_none() {
+        workspace.insert("dependencies", Item::Table(Table::new()));
+    }
+    let table = workspace
+        .get_mut("dependencies")
+        .and_then(Item::as_table_like_mut)
+        .ok_or_else(|| anyhow!("[workspace.dependencies] is not a table"))?;
+    match (table.get_mut(name), version) {
+        // A version given for an existing entry is the only change to it, so edit just that key
+        // and keep the entry's features and comments
+        (Some(item), Some(version)) => set_version(item, version),
+        (Some(_), None) => {}
+        (None, _) => {
+            table.insert(name, Item::Value(entry.to_value()));
+        }
+    }
+
+    let mut notes = Vec::new();
+    for declaration in &declarations {
+        let default_features = match (declaration.spec.default_features, entry.default_features) {
+            (None | Some(true), Some(false)) => Some(true),
+            (Some(false), None | Some(true)) => {
+                notes.push(format!(
+                    "{} now gets the default features of {name}, which the workspace entry enables",
+                    declaration.member
+                ));
+                None
+            }
+            _ => None,
+        };
+
+        let manifest = &mut manifests[declaration.manifest];
+        let Some(item) = table_at_mut(manifest.document.as_table_mut(), &declaration.table_path)
+            .and_then(|table| table.get_mut(name))
+        else {
+            continue;
+        };
+        match item.as_table_like_mut() {
+            Some(declared) => {
+                inherit(declared, default_features);
+                // Inline tables hold no comments, so tidy the spacing left by removed keys
+                if let Some(inline) = item.as_inline_table_mut() {
+                    inline.fmt();
+                }
+            }
+            None => {
+                let mut inherited = InlineTable::new();
+                inherited.insert("workspace", Value::from(true));
+                if let Some(default_features) = default_features {
+                    inherited.insert("default-features", Value::from(default_features));
+                }
+                // Keep any comment after the old declaration
+                let mut value = Value::InlineTable(inherited);
+                if let Some(suffix) = item.as_value().and_then(|old| old.decor().suffix()) {
+                    value.decor_mut().set_suffix(suffix.clone());
+                }
+                *item = Item::Value(value);
+            }
+        }
+    }
+
+    Ok(Promotion {
+        name: name.to_string(),
+        entry,
+        existing: existing.is_some(),
+        declarations,
+        notes,
+    })
+}